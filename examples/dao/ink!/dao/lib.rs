@@ -11,7 +11,14 @@ use ink_lang as ink;
 
 pub use self::dao::{
     Dao,
+    DaoConfig,
+    DaoParameters,
+    DaoStats,
+    DeadProposalPolicy,
+    DepositState,
     Proposal,
+    ProposalKind,
+    VotePosition,
     WEEK,
 };
 #[ink::contract]
@@ -26,10 +33,13 @@ mod dao {
     };
 
     use ink_prelude::vec::Vec;
-    use ink_primitives::Key;
-    use ink_prelude::collections::BTreeMap;
+    use ink_primitives::{
+        Key,
+        KeyPtr,
+    };
 
     use ink_env::{hash::{Keccak256, HashOutput}};
+    use ink_env::ReturnFlags;
     use ink_env::call::{
         build_call,
         Call,
@@ -37,6 +47,9 @@ mod dao {
         Selector, 
     };
     use scale::Output;
+    use core::convert::TryFrom;
+
+    use ink_lang::ToAccountId;
 
     use erc20::Erc20Ref;
 
@@ -48,6 +61,8 @@ mod dao {
 
     // The minimum debate period that a generic proposal can have
     const MIN_PROPOSAL_DEBATE_PERIOD: u64 = 2 * WEEK;
+    // The maximum debate period that a proposal can have
+    const MAX_PROPOSAL_DEBATE_PERIOD: u64 = 8 * WEEK;
     // The minimum debate period that a split proposal can have
     const QUORUM_HALVING_PERIOD: u64 = 25 * WEEK;
     // Period after which a proposal is closed
@@ -58,6 +73,16 @@ mod dao {
     // Denotes the maximum proposal deposit that can be given. It is given as
     // a fraction of total Ether spent plus balance of the DAO
     const MAX_DEPOSIT_DIVISOR: u128 = 100;
+    // The maximum number of voters returned by `voters_of`
+    const MAX_VOTERS_RETURNED: usize = 512;
+    // The largest `decimals` for which `10^decimals` fits in a `Balance`
+    const MAX_DECIMALS: u8 = 38;
+    // The number of entries kept in `quorum_met_history`
+    const MAX_QUORUM_MET_HISTORY: usize = 32;
+    // Selector of `newContract(address)` in the original DAO, which moves all funds
+    // to a new DAO contract. A proposal calling it needs the quorum of a proposal
+    // spending the whole treasury (see `is_high_quorum_call`).
+    const NEW_CONTRACT_SELECTOR: [u8; 4] = [0x68, 0x37, 0xff, 0x1e];
 
 
     /// A wrapper that allows us to encode a blob of bytes.
@@ -105,8 +130,257 @@ mod dao {
         // the accumulated sum of all current proposal deposits
         sum_of_proposal_deposits: u128, // u256;
 
+        // the accumulated sum of all current proposal deposits paid in governance tokens,
+        // see `DaoConfig::deposit_in_token`
+        sum_of_token_deposits: u128,
+
         //Voting power is represented by amount of Erc20 tokens
         token: Erc20Ref,
+
+        // True once the treasury has been moved to a successor contract.
+        // A migrated DAO accepts no new proposals or votes.
+        migrated: bool,
+
+        // Minimum time between two proposals of the same creator
+        proposal_cooldown: u64,
+        // The unix time of each creator's latest proposal
+        last_proposal_time: Mapping<AccountId, Timestamp>,
+
+        // Optional behaviour chosen at instantiation
+        config: DaoConfig,
+
+        // The token balance a voter had when casting their vote on a proposal.
+        // This is the weight removed from the tally when the vote is withdrawn.
+        vote_weights: Mapping<(u64, AccountId), u128>,
+
+        // Minimum deposit of proposals paying a recipient, if higher than `proposal_deposit`
+        recipient_min_deposits: Mapping<AccountId, u128>,
+
+        // The current vote of each shareholder on a proposal. Kept outside of
+        // `Proposal` so the size of a proposal does not grow with every vote.
+        votes: Mapping<(u64, AccountId), VotePosition>,
+
+        // The times an executed proposal reached quorum, oldest first.
+        // Holds at most `MAX_QUORUM_MET_HISTORY` entries.
+        quorum_met_history: Vec<Timestamp>,
+
+        // Upper limit of the gas an executed transaction is called with, 0 for no limit
+        max_execution_gas: u64,
+
+        // Number of proposals by outcome, returned by `stats`
+        stats: DaoStats,
+
+        // The unix time each account last withdrew its vote on a proposal with `un_vote`
+        unvoted_at: Mapping<(u64, AccountId), Timestamp>,
+
+        // The selectors a passed proposal may call on a recipient.
+        // Recipients without an entry can be called with any selector.
+        allowed_selectors: Mapping<AccountId, Vec<[u8; 4]>>,
+
+        // Number of votes cast on any proposal
+        vote_seq: u64,
+
+        // False until the first proposal, or while the curator sets up the DAO
+        // with `explicit_fueling`, see `close_fueling`
+        fueled: bool,
+    }
+
+    // The side a shareholder voted for
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum VotePosition {
+        Yes,
+        No,
+    }
+
+    // Optional settings of the DAO, supplied through `new_with_config`.
+    // `new` uses the default, which behaves like the original DAO.
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        SpreadAllocate,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct DaoConfig {
+        // Share of the deposit (in basis points) refunded for a proposal that
+        // missed quorum but came close to it. 0 slashes the whole deposit.
+        pub partial_refund_bps: u16,
+        // How close to quorum (in basis points of the required quorum) a failed
+        // proposal has to come to get the partial refund
+        pub partial_refund_threshold_bps: u16,
+        // Reject votes on proposals whose outcome can no longer change (see `is_decided`)
+        pub reject_votes_once_decided: bool,
+        // Decimals of the chain currency, used by `to_display_units`
+        pub decimals: u8,
+        // The curator may not vote, keeping it neutral on the proposals it manages
+        pub neutral_curator: bool,
+        // Reject a proposal identical (same kind and proposal hash) to an open one
+        pub reject_duplicate_proposals: bool,
+        // Where slashed deposits are sent, e.g. a burn or public goods address.
+        // `None` keeps them in the treasury.
+        pub slash_destination: Option<AccountId>,
+        // Whether proposals without any support can be closed early with
+        // `kill_dead_proposal`, and what happens to their deposit
+        pub dead_proposal_policy: DeadProposalPolicy,
+        // Time an account has to wait after `un_vote` before voting on the
+        // same proposal again, 0 for no cooldown
+        pub revote_cooldown: u64,
+        // Proposal deposits are paid in governance tokens instead of the chain currency.
+        // The deposit is pulled with `transfer_from`, so the DAO has to be approved first.
+        pub deposit_in_token: bool,
+        // The fueling period lasts until the curator calls `close_fueling`, instead of
+        // ending with the first proposal like in the original DAO
+        pub explicit_fueling: bool,
+    }
+
+    // Number of proposals created, and of the closed ones by outcome.
+    // Proposals that are still open are `total_created` minus the other three.
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        SpreadAllocate,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct DaoStats {
+        pub total_created: u64,
+        // Passed and executed
+        pub total_passed: u64,
+        // Closed by `execute_proposal` without being executed, including passed
+        // proposals whose call failed
+        pub total_failed: u64,
+        // Closed early by `kill_dead_proposal`
+        pub total_cancelled: u64,
+    }
+
+    // Handling of proposals nobody supports by the start of the pre-support time
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum DeadProposalPolicy {
+        // Proposals run until their deadline
+        Disabled,
+        // The proposal can be killed, and the deposit goes back to the creator
+        Refund,
+        // The proposal can be killed, and the deposit is slashed
+        Slash,
+    }
+
+    impl Default for DeadProposalPolicy {
+        fn default() -> Self {
+            DeadProposalPolicy::Disabled
+        }
+    }
+
+    //`SpreadAllocate` can not be derived for enums
+    impl SpreadAllocate for DeadProposalPolicy {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.advance_by(1);
+            Self::default()
+        }
+    }
+
+    // What became of a proposal's deposit, returned by `deposit_status`
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum DepositState {
+        // Held by the DAO until the proposal is resolved
+        Locked,
+        // Paid back to the creator in full
+        Refunded,
+        // Paid back in part after a near miss of the quorum, the rest was slashed
+        PartiallyRefunded { refunded: Balance },
+        // Kept in the treasury or sent to the `slash_destination`
+        Slashed,
+    }
+
+    impl Default for DepositState {
+        fn default() -> Self {
+            DepositState::Locked
+        }
+    }
+
+    //`SpreadAllocate` can not be derived for enums
+    impl SpreadAllocate for DepositState {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.advance_by(1);
+            Self::default()
+        }
+    }
+
+    // Every governance parameter of the DAO, returned by `parameters`
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct DaoParameters {
+        pub proposal_deposit: Balance,
+        pub min_quorum_divisor: u128,
+        pub min_proposal_debate_period: u64,
+        pub max_proposal_debate_period: u64,
+        // Time after the voting deadline in which a proposal can be executed
+        pub execute_proposal_period: u64,
+        pub pre_support_time: u64,
+        pub quorum_halving_period: u64,
+        pub proposal_cooldown: u64,
+        pub max_execution_gas: u64,
+        // The optional behaviour chosen at instantiation
+        pub config: DaoConfig,
     }
 
     // A proposal with `newCurator == false` represents a transaction
@@ -154,12 +428,18 @@ mod dao {
         yea: u128, // u256
         // Number of Tokens opposed to the proposal
         nay: u128,// u256
-        // Simple mapping to check if a shareholder has voted for it
-        voted_yes: BTreeMap<AccountId, bool>,
-        // Simple mapping to check if a shareholder has voted against it
-        voted_no: BTreeMap<AccountId, bool>,
+        // Every shareholder that has voted on the proposal, in order of their first vote
+        voters: Vec<AccountId>,
         // Address of the shareholder who created the proposal
         creator: AccountId,
+        // What the DAO does once the proposal passes
+        kind: ProposalKind,
+        // The time the proposal was executed, if it passed
+        executed_at: Option<Timestamp>,
+        // True while the curator has frozen the proposal, see `freeze_proposal`
+        frozen: bool,
+        // Whether the deposit is still locked, or how it was resolved
+        deposit_state: DepositState,
     }
 
     impl ink_storage::traits::PackedAllocate for Proposal {
@@ -168,6 +448,59 @@ mod dao {
         }
     }
 
+    // The action carried out by a passed proposal.
+    // `Transaction` is the proposal type of the original DAO. All other kinds
+    // change the DAO itself, so they have no recipient, amount or transaction data.
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        Clone,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum ProposalKind {
+        // Send `amount` to `recipient` and invoke the transaction data on it
+        Transaction,
+        // Replace the token used to weight votes
+        ChangeToken { new_token: AccountId },
+        // Move the treasury to a new version of the DAO and retire this one
+        Migrate { successor: AccountId },
+        // Add `recipient` to or remove it from the allowed recipients,
+        // as the curator does with `change_allowed_recipients`
+        ChangeAllowedRecipient { recipient: AccountId, allowed: bool },
+        // Replace the code of the DAO with the uploaded code of `code_hash`, keeping
+        // its storage, treasury and account. The new code has to read the storage of
+        // this version: the fields of `Dao` must keep their order and types, new ones
+        // go at the end and have to be written before they are read. Packed values,
+        // such as each `Proposal` in `proposals`, are decoded as a whole, so their
+        // fields can not change at all without migrating the stored values. New
+        // variants of stored enums go after the existing ones.
+        SetCodeHash { code_hash: Hash },
+        // Restore the minimum quorum divisor to its original value, undoing all halvings
+        ResetQuorumDivisor,
+    }
+
+    impl Default for ProposalKind {
+        fn default() -> Self {
+            ProposalKind::Transaction
+        }
+    }
+
+    //`SpreadAllocate` can not be derived for enums
+    impl SpreadAllocate for ProposalKind {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.advance_by(1);
+            Self::default()
+        }
+    }
+
     #[ink(event)]
     pub struct ProposalAdded {
         #[ink(topic)]
@@ -208,9 +541,24 @@ mod dao {
         ProposalCreationFailed,
         OutsideDeadline,
         TransactionFailed,
+        // Returned when a neutral curator votes. Curator-only messages return `NotCurator`
         CallerIsCurator,
         UnableToHalveQuorum,
         UnableToChangeDeposit,
+        ProposalsStillOpen,
+        DaoMigrated,
+        NotCurator,
+        InsufficientBalance,
+        TooSoon,
+        VoterHoldsTokens,
+        ProposalDecided,
+        NotCreator,
+        NotTokenholder,
+        ProposalNotDead,
+        // A vote that would not change the caller's position or weight
+        NoChange,
+        // The proposal is frozen by the curator
+        ProposalFrozen,
     }
 
     pub type Result<T> = core::result::Result<T, Error>;
@@ -219,14 +567,25 @@ mod dao {
         /// Constructor that initializes the `bool` value to the given `init_value`.
         #[ink(constructor)]
         pub fn new(curator: AccountId, proposal_deposit: Balance, token_contract_id: AccountId) -> Self {
+            Self::new_with_config(curator, proposal_deposit, token_contract_id, DaoConfig::default())
+        }
+
+        //NOTE: not a part of the original contract.
+        //Same as `new`, with the optional behaviour described by `config`
+        #[ink(constructor)]
+        pub fn new_with_config(curator: AccountId, proposal_deposit: Balance, token_contract_id: AccountId, config: DaoConfig) -> Self {
             ink_lang::utils::initialize_contract(|contract| {
-                Self::new_init(contract, curator, proposal_deposit, token_contract_id)
+                Self::new_init(contract, curator, proposal_deposit, token_contract_id, config)
             })
         }
 
-        fn new_init(&mut self, curator: AccountId, proposal_deposit: Balance, token_contract_id: AccountId) {
+        fn new_init(&mut self, curator: AccountId, proposal_deposit: Balance, token_contract_id: AccountId, config: DaoConfig) {
+
+            assert!(config.partial_refund_bps <= 10_000, "DAO: partial refund above 100%");
+            assert!(config.decimals <= MAX_DECIMALS, "DAO: decimals above 38");
 
             self.token = ink_env::call::FromAccountId::from_account_id(token_contract_id);
+            self.config = config;
 
             self.curator = curator;
             self.proposal_deposit = proposal_deposit;
@@ -244,31 +603,161 @@ mod dao {
         //u64 is more than large enough to represent the proposals that could likely exist.
         #[ink(message, payable)]
         pub fn new_proposal(&mut self, recipient: AccountId, amount: Balance, description: Vec<u8>, transaction_data: Vec<u8>, debating_period: u64) -> Result<u64> {
-            let caller = self.env().caller();
-            self.ensure_tokenholder(&caller);
+            let deposit = self.env().transferred_value();
+            self.add_proposal(recipient, amount, description, transaction_data, debating_period, deposit, ProposalKind::Transaction)
+        }
+
+        //NOTE: not a part of the original contract. Funds the treasury and creates a
+        //proposal in one call. Only `proposal_deposit` of the transferred value is locked
+        //as the deposit, the rest is added to the treasury. Fails if the treasury would
+        //not be able to pay `amount`.
+        #[ink(message, payable)]
+        pub fn fund_and_propose(&mut self, recipient: AccountId, amount: Balance, description: Vec<u8>, transaction_data: Vec<u8>, debating_period: u64) -> Result<u64> {
+            // a deposit paid in tokens does not take anything from the transferred value
+            let deposit = if self.config.deposit_in_token { 0 } else { self.required_deposit(recipient) };
+            let transferred = self.env().transferred_value();
+
+            // the transferred value is already part of the contract balance
+            if transferred < deposit || self.actual_balance() - deposit < amount {
+                return Err(Error::InsufficientBalance)
+            }
+
+            // only the deposit is locked, everything above it stays in the treasury
+            self.add_proposal(recipient, amount, description, transaction_data, debating_period, deposit, ProposalKind::Transaction)
+        }
+
+        //NOTE: not a part of the original contract. Creates a proposal that changes
+        //the DAO itself. The proposal targets the DAO's own address with no amount and
+        //no transaction data, so it is executed with empty `transaction_data`.
+        #[ink(message, payable)]
+        pub fn new_governance_proposal(&mut self, kind: ProposalKind, description: Vec<u8>, debating_period: u64) -> Result<u64> {
+            match kind {
+                ProposalKind::Transaction => return Err(Error::ProposalCreationFailed),
+                ProposalKind::Migrate { successor } => {
+                    if !self.allowed_recipients.get(successor).unwrap_or(false) {
+                        return Err(Error::ProposalCreationFailed)
+                    }
+                }
+                _ => {}
+            }
+
+            let contract_addr = self.env().account_id();
+            let deposit = self.env().transferred_value();
+            self.add_proposal(contract_addr, 0, description, Vec::new(), debating_period, deposit, kind)
+        }
+
+        //NOTE: not a part of the original contract. Creates a new proposal with the
+        //terms of a closed proposal that did not pass, and a new deadline.
+        //Only callable by the creator of the failed proposal, who pays a fresh deposit.
+        //Only the hash of the transaction data is stored, so the data has to be
+        //supplied again (as for `execute_proposal`) and must match the old proposal.
+        #[ink(message, payable)]
+        pub fn resubmit_proposal(&mut self, proposal_id: u64, transaction_data: Vec<u8>, debating_period: u64) -> Result<u64> {
+            let p = &self.proposals[proposal_id as usize];
+            if self.env().caller() != p.creator {
+                return Err(Error::NotCreator)
+            }
+            if p.open
+                || p.proposal_passed
+                || p.proposal_hash != hash_proposal(&p.recipient, &p.amount, &transaction_data) {
+                    return Err(Error::ProposalCreationFailed)
+            }
 
+            let (recipient, amount, description, kind) = (p.recipient, p.amount, p.description.clone(), p.kind.clone());
             let deposit = self.env().transferred_value();
+            self.add_proposal(recipient, amount, description, transaction_data, debating_period, deposit, kind)
+        }
+
+        //NOTE: not a part of the original contract.
+        //Runs the checks of `new_proposal` for the caller without creating a proposal,
+        //so they can be checked before paying the deposit. The deposit itself is not checked.
+        //`amount` is not validated by `new_proposal` either, the treasury is only
+        //checked at execution.
+        #[ink(message)]
+        pub fn validate_proposal(&self, recipient: AccountId, _amount: Balance, debating_period: u64) -> Result<()> {
+            if self.migrated {
+                return Err(Error::DaoMigrated)
+            }
+
+            let caller = self.env().caller();
+            if self.get_token_balance(&caller) == 0 {
+                return Err(Error::NotTokenholder)
+            }
 
+            self.check_proposal_terms(caller, recipient, debating_period)
+        }
+
+        // the checks shared by `add_proposal` and `validate_proposal`
+        fn check_proposal_terms(&self, caller: AccountId, recipient: AccountId, debating_period: u64) -> Result<()> {
             if !self.allowed_recipients.get(recipient).unwrap_or(false)
                 || debating_period < MIN_PROPOSAL_DEBATE_PERIOD 
-                || debating_period > 8 * WEEK 
-                || deposit < self.proposal_deposit 
+                || debating_period > MAX_PROPOSAL_DEBATE_PERIOD
                 || caller == self.env().account_id() {
                     return Err(Error::ProposalCreationFailed)
             }
 
-            // to prevent curator from halving quorum before first proposal
-            if self.proposals.len() == 1 { // initial length is 1 (see constructor)
-                self.last_time_min_quorum_met = self.env().block_timestamp();
+            let now = self.env().block_timestamp();
+            if let Some(last_proposal_time) = self.last_proposal_time.get(caller) {
+                if now < last_proposal_time + self.proposal_cooldown {
+                    return Err(Error::TooSoon)
+                }
             }
 
-            let proposal_id: u64 = self.proposals.len() as u64;
+            Ok(())
+        }
+
+        // `deposit` is the part of the transferred value locked as the proposal deposit.
+        // With `deposit_in_token` the deposit is pulled from the caller's tokens instead,
+        // and the transferred value stays in the treasury.
+        fn add_proposal(&mut self, recipient: AccountId, amount: Balance, description: Vec<u8>, transaction_data: Vec<u8>, debating_period: u64, deposit: Balance, kind: ProposalKind) -> Result<u64> {
+            if self.migrated {
+                return Err(Error::DaoMigrated)
+            }
+
+            let caller = self.env().caller();
+            self.ensure_tokenholder(&caller);
+
+            if !self.config.deposit_in_token && deposit < self.required_deposit(recipient) {
+                return Err(Error::ProposalCreationFailed)
+            }
+            self.check_proposal_terms(caller, recipient, debating_period)?;
 
             // let encodable = (recipient, amount, transaction_data); // Implements `scale::Encode`
             // let mut output = <Keccak256 as HashOutput>::Type::default(); // 256-bit buffer
             // ink_env::hash_encoded::<Keccak256, _>(&encodable, &mut output);
             let proposal_hash = hash_proposal(&recipient, &amount, &transaction_data);
 
+            // governance proposals all share the hash of (contract, 0, []), so the kind is compared as well
+            if self.config.reject_duplicate_proposals
+                && self.proposals.iter().any(|p| p.open && p.proposal_hash == proposal_hash && p.kind == kind) {
+                    return Err(Error::ProposalCreationFailed)
+            }
+
+            // pulled after all checks and before any change, since returning an error
+            // does not undo the call into the token
+            let deposit = if self.config.deposit_in_token {
+                let deposit = self.required_deposit(recipient);
+                if !self.token_transfer_from(caller, deposit) {
+                    return Err(Error::InsufficientBalance)
+                }
+                deposit
+            } else {
+                deposit
+            };
+
+            let now = self.env().block_timestamp();
+
+            // to prevent curator from halving quorum before normal operation starts
+            if !self.fueled {
+                self.last_time_min_quorum_met = now;
+                // without `explicit_fueling` the first proposal ends the fueling period
+                if !self.config.explicit_fueling {
+                    self.fueled = true;
+                }
+            }
+
+            let proposal_id: u64 = self.proposals.len() as u64;
+
             let p: Proposal = Proposal{
                 recipient: recipient,
                 amount: amount,
@@ -282,14 +771,19 @@ mod dao {
                 pre_support: false,
                 yea: 0,
                 nay: 0,
-                voted_yes: BTreeMap::new(),
-                voted_no: BTreeMap::new(),
+                voters: Vec::new(),
                 creator: caller,
+                kind: kind,
+                executed_at: None,
+                frozen: false,
+                deposit_state: DepositState::Locked,
             };
 
-            self.sum_of_proposal_deposits += deposit;
+            *self.deposit_sum_mut() += deposit;
             
             self.proposals.push(p);
+            self.stats.total_created += 1;
+            self.last_proposal_time.insert(caller, &now);
 
             //NOTE: because cross-contract calls are being used, emitting events does not work
             // self.env().emit_event(ProposalAdded {
@@ -317,23 +811,65 @@ mod dao {
 
 
         #[ink(message)]
-        pub fn vote(&mut self, proposal_id: u64, supports_proposal: bool) {
+        pub fn vote(&mut self, proposal_id: u64, supports_proposal: bool) -> Result<()> {
+            if self.migrated {
+                return Err(Error::DaoMigrated)
+            }
+
+            if self.proposals[proposal_id as usize].frozen {
+                return Err(Error::ProposalFrozen)
+            }
+
+            // a proposal closed by `kill_dead_proposal` is over before its deadline
+            if !self.proposals[proposal_id as usize].open {
+                return Err(Error::OutsideDeadline)
+            }
+            // checked by `remove_vote` as well, but a repeated vote is rejected before that
+            if self.env().block_timestamp() >= self.proposals[proposal_id as usize].voting_deadline {
+                return Err(Error::OutsideDeadline)
+            }
+
+            if self.config.reject_votes_once_decided && self.is_decided(proposal_id) {
+                return Err(Error::ProposalDecided)
+            }
+
             let caller = self.env().caller();
+            if caller == self.curator && !self.curator_can_vote() {
+                return Err(Error::CallerIsCurator)
+            }
 
-            self.un_vote(proposal_id);
+            if let Some(unvoted_at) = self.unvoted_at.get((proposal_id, caller)) {
+                if self.env().block_timestamp() < unvoted_at + self.config.revote_cooldown {
+                    return Err(Error::TooSoon)
+                }
+            }
 
+            let position = if supports_proposal { VotePosition::Yes } else { VotePosition::No };
             let caller_balance = self.get_token_balance(&caller);
 
+            // voting again with an unchanged balance would un-vote and re-vote to the same tally.
+            // A changed balance is a valid reason to vote again, it updates the vote's weight.
+            if self.votes.get((proposal_id, caller)) == Some(position)
+                && self.vote_weights.get((proposal_id, caller)) == Some(caller_balance) {
+                    return Err(Error::NoChange)
+            }
+
+            self.remove_vote(proposal_id, caller)?;
+
+            self.vote_weights.insert((proposal_id, caller), &caller_balance);
+            self.votes.insert((proposal_id, caller), &position);
+
             let mut p = &mut self.proposals[proposal_id as usize];
 
             if supports_proposal {
                 p.yea += caller_balance;
-                p.voted_yes.insert(caller, true);
             }else {
                 p.nay += caller_balance;
-                p.voted_no.insert(caller, true);
             }
 
+            if !p.voters.contains(&caller) {
+                p.voters.push(caller);
+            }
 
             let blocked_proposal = self.blocked.get(caller).unwrap_or(0);
             if  blocked_proposal == 0 {
@@ -345,38 +881,133 @@ mod dao {
             let voted_proposals = &mut self.voting_register.get(caller).unwrap_or(Vec::new());
             voted_proposals.push(proposal_id);
             self.voting_register.insert(caller, voted_proposals);
+
+            self.vote_seq += 1;
             
             // self.env().emit_event(Voted {
             //     proposal_id,
             //     position: supports_proposal,
             //     voter: caller,
             // });
+
+            Ok(())
+        }
+
+        //NOTE: not a part of the original contract.
+        //Sum of `yea` and `nay` over all open proposals (there is no abstain position).
+        //An account voting on several proposals is counted once per proposal, so this
+        //is the voting power cast, not the number of voters.
+        #[ink(message)]
+        pub fn total_active_engagement(&self) -> u128 {
+            self.proposals.iter()
+                .filter(|p| p.open)
+                .fold(0u128, |total, p| total.saturating_add(p.yea).saturating_add(p.nay))
+        }
+
+        //NOTE: not a part of the original contract.
+        //Signed difference of `yea` and `nay`, and whether `yea` is currently ahead.
+        //`None` for unknown proposals and for margins that do not fit into an `i128`.
+        #[ink(message)]
+        pub fn vote_margin(&self, proposal_id: u64) -> Option<(i128, bool)> {
+            let p = self.proposals.get(proposal_id as usize)?;
+            let yea = i128::try_from(p.yea).ok()?;
+            let nay = i128::try_from(p.nay).ok()?;
+            Some((yea.checked_sub(nay)?, p.yea > p.nay))
+        }
+
+        //NOTE: not a part of the original contract.
+        //Signed change of `actual_balance` if the proposal passes and is executed now.
+        //The deposit is not part of `actual_balance`, so refunding it does not change the
+        //treasury. Amounts beyond the range of `i128` saturate at `i128::MIN`.
+        #[ink(message)]
+        pub fn treasury_impact(&self, proposal_id: u64) -> i128 {
+            let p = &self.proposals[proposal_id as usize];
+            let spent = match p.kind {
+                ProposalKind::Transaction => p.amount,
+                ProposalKind::Migrate { .. } => self.actual_balance(),
+                _ => 0,
+            };
+            i128::try_from(spent).map(|spent| -spent).unwrap_or(i128::MIN)
+        }
+
+        //NOTE: not a part of the original contract.
+        //Additional yes votes the proposal needs to reach the quorum and to lead the no votes
+        #[ink(message)]
+        pub fn yea_needed_to_pass(&self, proposal_id: u64) -> u128 {
+            let p = &self.proposals[proposal_id as usize];
+            let required_quorum = self.min_quorum(p.amount);
+            required_quorum.max(p.nay.saturating_add(1)).saturating_sub(p.yea)
+        }
+
+        //NOTE: not a part of the original contract.
+        //True once `yea` has reached the quorum and leads `nay` by more than the
+        //tokens that have not been cast yet, so no further vote can change the outcome.
+        #[ink(message)]
+        pub fn is_decided(&self, proposal_id: u64) -> bool {
+            let p = &self.proposals[proposal_id as usize];
+
+            // the quorum of a proposal the treasury can not pay is not meaningful
+            if p.amount > self.actual_balance() {
+                return false;
+            }
+
+            let cast = p.yea + p.nay;
+            let remaining = self.get_token_total_supply().saturating_sub(cast);
+            p.yea >= self.min_quorum(p.amount)
+                && p.yea > p.nay
+                && p.yea - p.nay > remaining
         }
 
         #[ink(message)]
         pub fn un_vote(&mut self, proposal_id: u64) -> Result<()>{
             let caller = self.env().caller();
+            let had_voted = self.votes.get((proposal_id, caller)).is_some();
+            self.remove_vote(proposal_id, caller)?;
+
+            // starts the `revote_cooldown`. Changing a vote with `vote` does not.
+            if had_voted && self.config.revote_cooldown > 0 {
+                self.unvoted_at.insert((proposal_id, caller), &self.env().block_timestamp());
+            }
+            Ok(())
+        }
+
+        //NOTE: not a part of the original contract.
+        //Removes the vote of an account that no longer holds any tokens.
+        //Callable by anyone while the proposal is being voted on.
+        #[ink(message)]
+        pub fn prune_vote(&mut self, proposal_id: u64, voter: AccountId) -> Result<()> {
+            if self.get_token_balance(&voter) != 0 {
+                return Err(Error::VoterHoldsTokens)
+            }
+
+            self.remove_vote(proposal_id, voter)
+        }
+
+        fn remove_vote(&mut self, proposal_id: u64, caller: AccountId) -> Result<()> {
             let now = self.env().block_timestamp();
 
-            let caller_balance = self.get_token_balance(&caller);            
+            // remove the weight the vote was cast with, the balance may have changed since
+            let vote_weight = self.vote_weights.get((proposal_id, caller)).unwrap_or(0);
 
             let mut p = &mut self.proposals[proposal_id as usize];
 
-            if now >= p.voting_deadline {
+            if p.frozen {
+                return Err(Error::ProposalFrozen);
+            }
+
+            // a proposal closed by `kill_dead_proposal` is over before its deadline
+            if !p.open || now >= p.voting_deadline {
                 //NOTE: this is more specific than the .sol version.
                 //The .sol version uses `throw`
                 return Err(Error::OutsideDeadline);
             }
 
-            if *p.voted_yes.get(&caller).unwrap_or(&false) {
-                p.yea -= caller_balance;
-                p.voted_yes.insert(caller, false);
-            }
-            
-            if *p.voted_no.get(&caller).unwrap_or(&false) {
-                p.nay -= caller_balance;
-                p.voted_no.insert(caller, false);
+            match self.votes.get((proposal_id, caller)) {
+                Some(VotePosition::Yes) => p.yea -= vote_weight,
+                Some(VotePosition::No) => p.nay -= vote_weight,
+                None => return Ok(()),
             }
+            self.votes.remove((proposal_id, caller));
             Ok(())
         }
 
@@ -390,7 +1021,8 @@ mod dao {
             for i in 0..(voting_register.len()){
                 let prop_id = voting_register[i];
                 let p = &self.proposals[prop_id as usize];
-                if now < p.voting_deadline {
+                // the votes on a frozen proposal are kept until it is unfrozen
+                if p.open && now < p.voting_deadline && !p.frozen {
                     self.un_vote(prop_id).expect("unable to unvote");
                 }
                 
@@ -402,25 +1034,68 @@ mod dao {
             self.blocked.insert(caller, &0);
         }
 
-        fn verify_pre_support(&mut self, proposal_id: u64) {
+        //NOTE: not a part of the original contract.
+        //The proposals in the voting register of `voter` that are still open, not frozen and
+        //before their deadline, i.e. the ones `un_vote_all` would un-vote. Each is listed once,
+        //even if the voter changed their vote.
+        #[ink(message)]
+        pub fn unvotable_proposals(&self, voter: AccountId) -> Vec<u64> {
             let now = self.env().block_timestamp();
-            let mut p = &mut self.proposals[proposal_id as usize];
-            
-            if now < p.voting_deadline - PRE_SUPPORT_TIME {
-                p.pre_support = true;
-            }else{
-                p.pre_support = false;
+            let mut unvotable: Vec<u64> = Vec::new();
+            for prop_id in self.voting_register.get(voter).unwrap_or(Vec::new()) {
+                let p = &self.proposals[prop_id as usize];
+                if p.open && !p.frozen && now < p.voting_deadline && !unvotable.contains(&prop_id) {
+                    unvotable.push(prop_id);
+                }
             }
+            unvotable
         }
 
+        //NOTE: `verifyPreSupport` in the original contract.
+        //Records whether the majority supports the proposal. Only changes the flag
+        //before the last `PRE_SUPPORT_TIME` of the voting period, after that the
+        //recorded support is final. Returns the updated flag.
         #[ink(message)]
-        //TODO: turn function_selector back to [u8; 4] -- edited because UI does not work with it
-        pub fn execute_proposal(&mut self, proposal_id: u64, function_selector: Vec<u8>, transaction_data: Vec<u8>, gas_limit: u64) -> Result<()>{
-            let now = self.env().block_timestamp();
-
-            let p = &self.proposals[proposal_id as usize];
+        pub fn refresh_pre_support(&mut self, proposal_id: u64) -> bool {
+            let pre_support = self.pre_support_status(proposal_id);
+            self.proposals[proposal_id as usize].pre_support = pre_support;
+            pre_support
+        }
+
+        //NOTE: not a part of the original contract.
+        //The flag `refresh_pre_support` would record now, false for unknown proposals
+        #[ink(message)]
+        pub fn pre_support_status(&self, proposal_id: u64) -> bool {
+            let p = match self.proposals.get(proposal_id as usize) {
+                Some(p) => p,
+                None => return false,
+            };
+
+            if p.open && self.env().block_timestamp() < p.voting_deadline - PRE_SUPPORT_TIME {
+                p.yea > p.nay
+            } else {
+                p.pre_support
+            }
+        }
+
+        #[ink(message)]
+        //TODO: turn function_selector back to [u8; 4] -- edited because UI does not work with it
+        pub fn execute_proposal(&mut self, proposal_id: u64, function_selector: Vec<u8>, transaction_data: Vec<u8>, gas_limit: u64) -> Result<()>{
+            let now = self.env().block_timestamp();
+
+            if self.proposals[proposal_id as usize].frozen {
+                return Err(Error::ProposalFrozen)
+            }
+
+            self.refresh_pre_support(proposal_id);
+            let p = &self.proposals[proposal_id as usize];
 
             if p.open && now > p.voting_deadline + EXECUTE_PROPOSAL_PERIOD {
+                // a passed proposal whose call failed has already refunded its deposit
+                if !p.proposal_passed {
+                    self.slash_deposit(p.proposal_deposit);
+                    self.proposals[proposal_id as usize].deposit_state = DepositState::Slashed;
+                }
                 self.close_proposal(proposal_id);
                 return Ok(())
             }
@@ -436,68 +1111,177 @@ mod dao {
                     return Err(Error::ProposalExecutionFailed)
                 }
 
+            // changing the token while other votes are running would weight
+            // them with two different tokens
+            match p.kind {
+                ProposalKind::ChangeToken { .. } => {
+                    if self.has_other_open_proposals(proposal_id) {
+                        return Err(Error::ProposalsStillOpen)
+                    }
+                }
+                // the successor may have been removed from the whitelist since the proposal was made
+                ProposalKind::Migrate { successor } => {
+                    if !self.allowed_recipients.get(successor).unwrap_or(false) {
+                        return Err(Error::ProposalExecutionFailed)
+                    }
+                }
+                // removing a recipient would make the open proposals paying it fail
+                ProposalKind::ChangeAllowedRecipient { recipient, allowed: false } => {
+                    if self.has_other_open_proposals_for(proposal_id, recipient) {
+                        return Err(Error::ProposalsStillOpen)
+                    }
+                }
+                ProposalKind::ChangeAllowedRecipient { .. }
+                | ProposalKind::SetCodeHash { .. }
+                | ProposalKind::ResetQuorumDivisor
+                | ProposalKind::Transaction => {}
+            }
+
             if !self.allowed_recipients.get(p.recipient).unwrap_or(false) {
                 // transfer the payment into the payee's account
-                if self.env().transfer(p.creator, p.proposal_deposit).is_err() {
+                if !self.transfer_deposit(p.creator, p.proposal_deposit) {
                     panic!("unable to return deposit")
                 }
 
+                self.proposals[proposal_id as usize].deposit_state = DepositState::Refunded;
                 self.close_proposal(proposal_id);
 
                 return Ok(());
             }
 
+            // rejected before anything is changed, the call is only made after the
+            // deposit is refunded and the proposal is marked as passed
+            if p.kind == ProposalKind::Transaction && !self.is_selector_allowed(p.recipient, &function_selector) {
+                return Err(Error::ProposalExecutionFailed)
+            }
+
             let mut proposal_check = true;
 
-            if p.amount > self.actual_balance() || p.pre_support == false{
+            // a proposal without an amount only calls the recipient, so it does not
+            // depend on the treasury being able to cover anything
+            let spends_treasury = p.amount > 0;
+            let affordable = !spends_treasury || p.amount <= self.actual_balance();
+            if p.pre_support == false{
                 proposal_check = false;
             }
 
             let quorum = p.yea;
-            if transaction_data.len() >= 4 && transaction_data[0] == 0x68
-                && transaction_data[1] == 0x37 && transaction_data[2] == 0xff
-                && transaction_data[3] == 0x1e
+            if (is_high_quorum_call(&transaction_data) || is_high_quorum_kind(&p.kind))
                 && quorum < self.min_quorum(self.actual_balance()) {
                     proposal_check = false
             }
 
-            if quorum >= self.min_quorum(p.amount){
-                if self.env().transfer(p.creator, p.proposal_deposit).is_err() {
+            // the voters approved a payment the treasury can no longer cover. The proposal
+            // stays open, so it can still be executed if the treasury is refilled in time.
+            // Checked against the base quorum, the quorum for the amount grows as the
+            // treasury shrinks and can not be reached at all once it is empty.
+            if !affordable && proposal_check && quorum >= self.min_quorum(0) && p.yea > p.nay {
+                return Err(Error::InsufficientBalance)
+            }
+            if !affordable {
+                proposal_check = false;
+            }
+
+            let required_quorum = self.min_quorum(p.amount);
+            let mut refund = 0;
+            if quorum >= required_quorum {
+                refund = p.proposal_deposit;
+                if !self.transfer_deposit(p.creator, refund) {
                     panic!("unable to return deposit")
                 }
 
                 self.last_time_min_quorum_met = now;
+                self.record_quorum_met(now);
 
-                if quorum > self.token.total_supply() / 7{
+                if quorum > self.get_token_total_supply() / 7{
                     self.min_quorum_divisor = 7;
                 }
+            } else if self.is_near_miss(quorum, required_quorum) {
+                // refund part of the deposit, the rest stays in the treasury
+                refund = p.proposal_deposit * self.config.partial_refund_bps as u128 / 10_000;
+                if !self.transfer_deposit(p.creator, refund) {
+                    panic!("unable to return deposit")
+                }
+            }
+
+            if refund < p.proposal_deposit {
+                self.slash_deposit(p.proposal_deposit - refund);
             }
 
-            if quorum >= self.min_quorum(p.amount) && p.yea > p.nay && proposal_check {
+            let deposit_state = if refund == p.proposal_deposit {
+                DepositState::Refunded
+            } else if refund > 0 {
+                DepositState::PartiallyRefunded { refunded: refund }
+            } else {
+                DepositState::Slashed
+            };
+            let passed = quorum >= self.min_quorum(p.amount) && p.yea > p.nay && proposal_check;
+            self.proposals[proposal_id as usize].deposit_state = deposit_state;
+
+            if passed {
                 // we are setting this here before the CALL() value transfer to
                 // assure that in the case of a malicious recipient contract trying
                 // to call executeProposal() recursively money can't be transferred
                 // multiple times out of the DAO
-                {
+                let kind = {
                     let p_mut = &mut self.proposals[proposal_id as usize];
                     p_mut.proposal_passed = true;
+                    p_mut.kind.clone()
+                };
+
+                match kind {
+                    ProposalKind::Transaction => {
+                        //TODO: remove this once the UI is fixed
+                        let mut tmp_selector: [u8; 4] = [0;4];
+                        tmp_selector[0] = function_selector[0];
+                        tmp_selector[1] = function_selector[1];
+                        tmp_selector[2] = function_selector[2];
+                        tmp_selector[3] = function_selector[3];
+
+                        // this call is as generic as any transaction. It sends all gas and
+                        // can do everything a transaction can do. It can be used to reenter
+                        // the DAO. The `p.proposalPassed` variable prevents the call from 
+                        // reaching this line again
+                        let gas_limit = self.execution_gas_limit(gas_limit);
+                        let res = self.invoke_transaction(proposal_id, &tmp_selector, &transaction_data, &gas_limit);
+                        if res.is_err(){
+                            return res;
+                        }
+                    }
+                    ProposalKind::ChangeToken { new_token } => {
+                        self.token = ink_env::call::FromAccountId::from_account_id(new_token);
+                    }
+                    ProposalKind::Migrate { successor } => {
+                        // release this proposal's deposit first, it has already been refunded.
+                        // It counts as executed, a failed transfer below reverts everything.
+                        self.proposals[proposal_id as usize].executed_at = Some(now);
+                        self.close_proposal(proposal_id);
+
+                        // the deposit has been refunded and the proposal closed, returning
+                        // an error would keep these changes
+                        let amount = self.actual_balance();
+                        if self.env().transfer(successor, amount).is_err() {
+                            panic!("unable to transfer the treasury to the successor")
+                        }
+                        self.migrated = true;
+                    }
+                    ProposalKind::ChangeAllowedRecipient { recipient, allowed } => {
+                        self.allowed_recipients.insert(recipient, &allowed);
+                    }
+                    ProposalKind::SetCodeHash { code_hash } => {
+                        // as for a migration, returning an error would keep the refund
+                        // and `proposal_passed`
+                        if ink_env::set_code_hash(code_hash.as_ref()).is_err() {
+                            panic!("unable to set the code hash")
+                        }
+                    }
+                    ProposalKind::ResetQuorumDivisor => {
+                        self.min_quorum_divisor = 7; // the value set by the constructor
+                        self.last_time_min_quorum_met = now;
+                    }
                 }
 
-                //TODO: remove this once the UI is fixed
-                let mut tmp_selector: [u8; 4] = [0;4];
-                tmp_selector[0] = function_selector[0];
-                tmp_selector[1] = function_selector[1];
-                tmp_selector[2] = function_selector[2];
-                tmp_selector[3] = function_selector[3];
-
-                // this call is as generic as any transaction. It sends all gas and
-                // can do everything a transaction can do. It can be used to reenter
-                // the DAO. The `p.proposalPassed` variable prevents the call from 
-                // reaching this line again
-                let res = self.invoke_transaction(proposal_id, &tmp_selector, &transaction_data, &gas_limit);
-                if res.is_err(){
-                    return res;
-                }
+                self.proposals[proposal_id as usize].executed_at = Some(now);
             }
 
             self.close_proposal(proposal_id);
@@ -511,11 +1295,156 @@ mod dao {
             Ok(())
         }
 
+        //NOTE: not a part of the original contract.
+        //Whether the deposit of a proposal is still locked, refunded or slashed,
+        //together with the deposit. `None` for unknown proposals.
+        #[ink(message)]
+        pub fn deposit_status(&self, proposal_id: u64) -> Option<(DepositState, Balance)> {
+            if proposal_id == 0 {
+                return None
+            }
+            let p = self.proposals.get(proposal_id as usize)?;
+            Some((p.deposit_state, p.proposal_deposit))
+        }
+
+        //NOTE: not a part of the original contract.
+        //Freezes a single suspicious proposal: it can not be voted on or executed
+        //until `unfreeze_proposal`. The rest of the DAO is not affected.
+        #[ink(message)]
+        pub fn freeze_proposal(&mut self, proposal_id: u64) -> Result<()> {
+            self.set_frozen(proposal_id, true)
+        }
+
+        //NOTE: not a part of the original contract.
+        //Lifts the freeze of `freeze_proposal`
+        #[ink(message)]
+        pub fn unfreeze_proposal(&mut self, proposal_id: u64) -> Result<()> {
+            self.set_frozen(proposal_id, false)
+        }
+
+        fn set_frozen(&mut self, proposal_id: u64, frozen: bool) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+
+            let p = &mut self.proposals[proposal_id as usize];
+            if p.frozen == frozen {
+                return Err(Error::NoChange)
+            }
+            p.frozen = frozen;
+            Ok(())
+        }
+
+        //NOTE: not a part of the original contract.
+        //Dry run of `execute_proposal` for keepers: runs all checks and the call, then
+        //reverts every change, including the ones made by the called contract.
+        //Returns `Ok` only if the proposal would pass and its call succeed.
+        #[ink(message)]
+        pub fn try_execute(&mut self, proposal_id: u64, function_selector: Vec<u8>, transaction_data: Vec<u8>, gas_limit: u64) -> Result<()> {
+            let result = self.execute_for_dry_run(proposal_id, function_selector, transaction_data, gas_limit);
+
+            // returning with the revert flag discards the storage changes and transfers
+            // made by this call, while the caller still receives `result`
+            ink_env::return_value::<Result<()>>(ReturnFlags::default().set_reverted(true), &result)
+        }
+
+        // Executes the proposal like `execute_proposal`, but only succeeds if the proposal
+        // passed and its call succeeded. The changes are kept, `try_execute` reverts them.
+        fn execute_for_dry_run(&mut self, proposal_id: u64, function_selector: Vec<u8>, transaction_data: Vec<u8>, gas_limit: u64) -> Result<()> {
+            self.execute_proposal(proposal_id, function_selector, transaction_data, gas_limit)?;
+            // closed without passing, e.g. without quorum or after the execution period
+            if self.proposals[proposal_id as usize].proposal_passed {
+                Ok(())
+            } else {
+                Err(Error::ProposalExecutionFailed)
+            }
+        }
+
+        //NOTE: not a part of the original contract.
+        //Closes an open proposal without a single yes vote once the pre-support time
+        //before its deadline has started, since it can no longer pass. The deposit is
+        //refunded or slashed according to the `dead_proposal_policy`. Callable by anyone.
+        #[ink(message)]
+        pub fn kill_dead_proposal(&mut self, proposal_id: u64) -> Result<()> {
+            let now = self.env().block_timestamp();
+            let p = &self.proposals[proposal_id as usize];
+
+            if p.frozen {
+                return Err(Error::ProposalFrozen)
+            }
+
+            if self.config.dead_proposal_policy == DeadProposalPolicy::Disabled
+                || !p.open
+                || p.yea > 0
+                || now < p.voting_deadline.saturating_sub(PRE_SUPPORT_TIME) {
+                    return Err(Error::ProposalNotDead)
+            }
+
+            let deposit_state = if self.config.dead_proposal_policy == DeadProposalPolicy::Refund {
+                if !self.transfer_deposit(p.creator, p.proposal_deposit) {
+                    panic!("unable to return deposit")
+                }
+                DepositState::Refunded
+            } else {
+                self.slash_deposit(p.proposal_deposit);
+                DepositState::Slashed
+            };
+
+            self.proposals[proposal_id as usize].deposit_state = deposit_state;
+            self.close_proposal(proposal_id);
+            // counted as failed by `close_proposal`, but it was cancelled before its deadline
+            self.stats.total_failed -= 1;
+            self.stats.total_cancelled += 1;
+            Ok(())
+        }
+
+        // Keeps the slashed part of a deposit in the treasury, or sends it to the
+        // configured `slash_destination`. Has to be called before the proposal is closed.
+        fn slash_deposit(&self, amount: Balance) {
+            if let Some(to) = self.config.slash_destination {
+                if !self.transfer_deposit(to, amount) {
+                    panic!("unable to redirect slashed deposit")
+                }
+            }
+        }
+
+        // true if a quorum that was missed qualifies for a partial deposit refund
+        fn is_near_miss(&self, quorum: u128, required_quorum: u128) -> bool {
+            self.config.partial_refund_bps > 0
+                && quorum.saturating_mul(10_000)
+                    >= required_quorum.saturating_mul(self.config.partial_refund_threshold_bps as u128)
+        }
+
+        fn has_other_open_proposals(&self, proposal_id: u64) -> bool {
+            self.proposals.iter()
+                .enumerate()
+                .any(|(id, p)| p.open && id as u64 != proposal_id)
+        }
+
+        // true if another open proposal pays `recipient` or migrates to it
+        fn has_other_open_proposals_for(&self, proposal_id: u64, recipient: AccountId) -> bool {
+            self.proposals.iter()
+                .enumerate()
+                .filter(|(id, p)| p.open && *id as u64 != proposal_id)
+                .any(|(_, p)| p.recipient == recipient || p.kind == ProposalKind::Migrate { successor: recipient })
+        }
+
         fn close_proposal(&mut self, proposal_id: u64) {
             let p = &mut self.proposals[proposal_id as usize];
 
             if p.open {
-                self.sum_of_proposal_deposits -= p.proposal_deposit;
+                if self.config.deposit_in_token {
+                    self.sum_of_token_deposits -= p.proposal_deposit;
+                } else {
+                    self.sum_of_proposal_deposits -= p.proposal_deposit;
+                }
+
+                // a passed proposal whose call failed was not executed
+                if p.executed_at.is_some() {
+                    self.stats.total_passed += 1;
+                } else {
+                    self.stats.total_failed += 1;
+                }
             }
 
             p.open = false;
@@ -547,12 +1476,192 @@ mod dao {
             Ok(())
         }
 
+        //NOTE: not a part of the original contract. Maintenance message.
+        //Returns `(stored, recomputed)`: the stored `sum_of_proposal_deposits` (or
+        //`sum_of_token_deposits`) and the sum of the deposits of all open proposals.
+        //The two differ if the incremental accounting has drifted.
+        #[ink(message)]
+        pub fn audit_deposits(&self) -> (u128, u128) {
+            let stored = if self.config.deposit_in_token {
+                self.sum_of_token_deposits
+            } else {
+                self.sum_of_proposal_deposits
+            };
+            (stored, self.open_proposal_deposits())
+        }
+
+        //NOTE: not a part of the original contract. Maintenance message.
+        //Resets `sum_of_proposal_deposits` to the recomputed sum of open deposits
+        #[ink(message)]
+        pub fn reconcile_deposits(&mut self) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+
+            *self.deposit_sum_mut() = self.open_proposal_deposits();
+            Ok(())
+        }
+
+        // The sum of locked deposits in the denomination deposits are paid in
+        fn deposit_sum_mut(&mut self) -> &mut u128 {
+            if self.config.deposit_in_token {
+                &mut self.sum_of_token_deposits
+            } else {
+                &mut self.sum_of_proposal_deposits
+            }
+        }
+
+        // Pays out `amount` of the locked deposits, in the chain currency or in
+        // governance tokens. Returns false if the transfer failed.
+        fn transfer_deposit(&self, to: AccountId, amount: Balance) -> bool {
+            if self.config.deposit_in_token {
+                self.token_transfer(to, amount)
+            } else {
+                self.env().transfer(to, amount).is_ok()
+            }
+        }
+
+        fn open_proposal_deposits(&self) -> u128 {
+            self.proposals.iter()
+                .filter(|p| p.open)
+                .map(|p| p.proposal_deposit)
+                .sum()
+        }
+
+        //NOTE: not a part of the original contract.
+        //All governance parameters, including the constants, in one call
+        #[ink(message)]
+        pub fn parameters(&self) -> DaoParameters {
+            DaoParameters {
+                proposal_deposit: self.proposal_deposit,
+                min_quorum_divisor: self.min_quorum_divisor,
+                min_proposal_debate_period: MIN_PROPOSAL_DEBATE_PERIOD,
+                max_proposal_debate_period: MAX_PROPOSAL_DEBATE_PERIOD,
+                execute_proposal_period: EXECUTE_PROPOSAL_PERIOD,
+                pre_support_time: PRE_SUPPORT_TIME,
+                quorum_halving_period: QUORUM_HALVING_PERIOD,
+                proposal_cooldown: self.proposal_cooldown,
+                max_execution_gas: self.max_execution_gas,
+                config: self.config.clone(),
+            }
+        }
+
+        //NOTE: not a part of the original contract.
+        //Minimum time (in seconds) a creator has to wait between two proposals
+        #[ink(message)]
+        pub fn proposal_cooldown(&self) -> u64 {
+            self.proposal_cooldown
+        }
+
+        //NOTE: not a part of the original contract.
+        #[ink(message)]
+        pub fn set_proposal_cooldown(&mut self, proposal_cooldown: u64) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+
+            self.proposal_cooldown = proposal_cooldown;
+            Ok(())
+        }
+
+        //NOTE: not a part of the original contract.
+        //Upper limit of the gas `execute_proposal` calls a recipient with, 0 for no limit
+        #[ink(message)]
+        pub fn max_execution_gas(&self) -> u64 {
+            self.max_execution_gas
+        }
+
+        //NOTE: not a part of the original contract.
+        #[ink(message)]
+        pub fn set_max_execution_gas(&mut self, max_execution_gas: u64) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+
+            self.max_execution_gas = max_execution_gas;
+            Ok(())
+        }
+
+        // The gas limit the executor supplied, capped at `max_execution_gas`.
+        // A limit of 0 forwards all remaining gas, so it is capped as well.
+        fn execution_gas_limit(&self, gas_limit: u64) -> u64 {
+            if self.max_execution_gas == 0 {
+                return gas_limit
+            }
+            if gas_limit == 0 {
+                return self.max_execution_gas
+            }
+            gas_limit.min(self.max_execution_gas)
+        }
+
+        //NOTE: not a part of the original contract.
+        //The selectors passed proposals may call on `recipient`, empty if any selector is allowed
+        #[ink(message)]
+        pub fn allowed_selectors(&self, recipient: AccountId) -> Vec<[u8; 4]> {
+            self.allowed_selectors.get(recipient).unwrap_or_default()
+        }
+
+        //NOTE: not a part of the original contract.
+        //Restricts the selectors passed proposals may call on `recipient`.
+        //An empty list lifts the restriction.
+        #[ink(message)]
+        pub fn set_allowed_selectors(&mut self, recipient: AccountId, selectors: Vec<[u8; 4]>) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+
+            if selectors.is_empty() {
+                self.allowed_selectors.remove(recipient);
+            } else {
+                self.allowed_selectors.insert(recipient, &selectors);
+            }
+            Ok(())
+        }
+
+        // `execute_proposal` only uses the first four bytes of `function_selector`
+        fn is_selector_allowed(&self, recipient: AccountId, function_selector: &[u8]) -> bool {
+            match self.allowed_selectors.get(recipient) {
+                None => true,
+                Some(selectors) => selectors.iter()
+                    .any(|selector| function_selector.get(..4) == Some(&selector[..])),
+            }
+        }
+
+        //NOTE: not a part of the original contract.
+        //The minimum deposit override of proposals paying `recipient`, if any
+        #[ink(message)]
+        pub fn recipient_min_deposit(&self, recipient: AccountId) -> Option<u128> {
+            self.recipient_min_deposits.get(recipient)
+        }
+
+        //NOTE: not a part of the original contract.
+        //Proposals paying `recipient` require a deposit of at least `min_deposit`.
+        //The base `proposal_deposit` still applies if it is higher. 0 removes the override.
+        #[ink(message)]
+        pub fn set_recipient_min_deposit(&mut self, recipient: AccountId, min_deposit: u128) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+
+            if min_deposit == 0 {
+                self.recipient_min_deposits.remove(recipient);
+            } else {
+                self.recipient_min_deposits.insert(recipient, &min_deposit);
+            }
+            Ok(())
+        }
+
+        fn required_deposit(&self, recipient: AccountId) -> u128 {
+            let min_deposit = self.recipient_min_deposits.get(recipient).unwrap_or(0);
+            self.proposal_deposit.max(min_deposit)
+        }
+
         #[ink(message)]
         pub fn change_allowed_recipients(&mut self, recipient: AccountId, allowed: bool) -> Result<()> {
             let caller = self.env().caller();
 
             if caller != self.curator{
-                return Err(Error::CallerIsCurator);
+                return Err(Error::NotCurator);
             }
 
             self.allowed_recipients.insert(recipient, &allowed);
@@ -579,6 +1688,12 @@ mod dao {
             &mut self,
             proposal_id: u64, function_selector: &[u8; 4], transaction_data: &Vec<u8>, gas_limit: &u64) -> Result<()> {
             let p = &self.proposals[proposal_id as usize];
+
+            //calls stubbed by the tests do not reach the recipient
+            #[cfg(test)]
+            if let Some(result) = stubbed_call(p.recipient, *function_selector, p.amount) {
+                return result
+            }
             
             let result = build_call::<<Self as ::ink_lang::reflect::ContractEnv>::Env>()
                 .call_type(
@@ -601,11 +1716,65 @@ mod dao {
         }
 
         fn min_quorum(&self, value: u128) -> u128 {
-            let total_supply = self.token.total_supply();
+            // a zero divisor should never be stored; treat it as an unreachable quorum
+            // instead of panicking on the division
+            if self.min_quorum_divisor == 0 {
+                return u128::MAX;
+            }
+
+            let total_supply = self.get_token_total_supply();
+            // nothing is spent, so the quorum does not depend on the treasury
+            // (which may be empty)
+            if value == 0 {
+                return total_supply / self.min_quorum_divisor;
+            }
+            // an empty treasury can not pay anything
+            if self.actual_balance() == 0 {
+                return u128::MAX;
+            }
+
             return total_supply / self.min_quorum_divisor +
                 (value * total_supply) / (3 * (self.actual_balance()));
         }
 
+        //NOTE: not a part of the original contract.
+        //The base quorum (`total_supply / min_quorum_divisor`) in basis points of the
+        //total supply, e.g. 1428 for the initial divisor of 7. Proposals spending the
+        //treasury need more (see `min_quorum`). Returns `u16::MAX` for a zero divisor,
+        //for which no quorum can be reached.
+        #[ink(message)]
+        pub fn quorum_percentage_bps(&self) -> u16 {
+            if self.min_quorum_divisor == 0 {
+                return u16::MAX;
+            }
+            (10_000 / self.min_quorum_divisor) as u16
+        }
+
+        //NOTE: not a part of the original contract, which ends fueling at a fixed `closingTime`.
+        //Ends the set up of the DAO by the curator. Until then every new proposal restarts the
+        //quorum timing and the quorum can not be halved. Only needed with `explicit_fueling`,
+        //otherwise the first proposal ends the fueling period.
+        #[ink(message)]
+        pub fn close_fueling(&mut self) -> Result<()> {
+            if self.env().caller() != self.curator {
+                return Err(Error::NotCurator);
+            }
+            if self.fueled {
+                return Err(Error::NoChange);
+            }
+
+            self.fueled = true;
+            self.last_time_min_quorum_met = self.env().block_timestamp();
+            Ok(())
+        }
+
+        //NOTE: not a part of the original contract.
+        //True once the fueling period has ended, see `close_fueling`
+        #[ink(message)]
+        pub fn is_fueled(&self) -> bool {
+            self.fueled
+        }
+
         #[ink(message)]
         pub fn halve_min_quorum(&mut self) -> Result<()> {
             let caller = self.env().caller();
@@ -615,9 +1784,12 @@ mod dao {
             // between the calls
             if (self.last_time_min_quorum_met < ( now - QUORUM_HALVING_PERIOD) || caller == self.curator) 
                 && self.last_time_min_quorum_met < (now - MIN_PROPOSAL_DEBATE_PERIOD)
-                && self.proposals.len() > 1 {
+                && self.fueled {
+                // the divisor must never overflow to zero
+                let min_quorum_divisor = self.min_quorum_divisor.checked_mul(2)
+                    .ok_or(Error::UnableToHalveQuorum)?;
                 self.last_time_min_quorum_met = now;
-                self.min_quorum_divisor *= 2;
+                self.min_quorum_divisor = min_quorum_divisor;
                 return Ok(());
             }
 
@@ -649,6 +1821,40 @@ mod dao {
             self.get_or_modify_blocked(self.env().caller())
         }
 
+        //NOTE: not a part of the original contract.
+        //The open proposal blocking `account` with its voting deadline, the earliest time
+        //it can close. `None` if the account is not blocked.
+        #[ink(message)]
+        pub fn blocking_proposal(&self, account: AccountId) -> Option<(u64, Timestamp)> {
+            let prop_id = self.blocked.get(account).unwrap_or(0);
+            if prop_id == 0 {
+                return None
+            }
+
+            let p = &self.proposals[prop_id as usize];
+            if !p.open {
+                return None
+            }
+            Some((prop_id, p.voting_deadline))
+        }
+
+        //only compiles when *not* running tests
+        #[cfg(not(test))]
+        fn get_releasable(&self, wallet: AccountId) -> Option<Balance> {
+            build_call::<<Self as ::ink_lang::reflect::ContractEnv>::Env>()
+                .call_type(Call::new().callee(wallet))
+                .exec_input(ExecutionInput::new(Selector::new(ink_lang::selector_bytes!("releasable"))))
+                .returns::<Balance>()
+                .fire()
+                .ok()
+        }
+
+        //only compiles when running tests
+        #[cfg(test)]
+        fn get_releasable(&self, wallet: AccountId) -> Option<Balance> {
+            TEST_RELEASABLE.with(|releasable| releasable.borrow().get(&wallet).copied())
+        }
+
         //only compiles when *not* running tests
         #[cfg(not(test))]
         fn get_token_balance(&self, caller: &AccountId) -> Balance {
@@ -657,8 +1863,47 @@ mod dao {
 
         //only compiles when running tests
         #[cfg(test)]
-        fn get_token_balance(&self, _: &AccountId) -> Balance {
-            1
+        fn get_token_balance(&self, caller: &AccountId) -> Balance {
+            TEST_TOKEN_BALANCES.with(|balances| *balances.borrow().get(caller).unwrap_or(&1))
+        }
+
+        //only compiles when *not* running tests
+        #[cfg(not(test))]
+        fn token_transfer(&self, to: AccountId, amount: Balance) -> bool {
+            // a second reference to the token, since `transfer` needs a mutable one
+            let mut token: Erc20Ref = ink_env::call::FromAccountId::from_account_id(self.token.to_account_id());
+            token.transfer(to, amount).is_ok()
+        }
+
+        //only compiles when running tests
+        #[cfg(test)]
+        fn token_transfer(&self, to: AccountId, amount: Balance) -> bool {
+            move_test_tokens(self.env().account_id(), to, amount)
+        }
+
+        //only compiles when *not* running tests
+        #[cfg(not(test))]
+        fn token_transfer_from(&mut self, from: AccountId, amount: Balance) -> bool {
+            let dao = self.env().account_id();
+            self.token.transfer_from(from, dao, amount).is_ok()
+        }
+
+        //only compiles when running tests
+        #[cfg(test)]
+        fn token_transfer_from(&mut self, from: AccountId, amount: Balance) -> bool {
+            move_test_tokens(from, self.env().account_id(), amount)
+        }
+
+        //only compiles when *not* running tests
+        #[cfg(not(test))]
+        fn get_token_total_supply(&self) -> Balance {
+            self.token.total_supply()
+        }
+
+        //only compiles when running tests
+        #[cfg(test)]
+        fn get_token_total_supply(&self) -> Balance {
+            TEST_TOKEN_TOTAL_SUPPLY.with(|total_supply| total_supply.get())
         }
 
         //NOTE: is a modifer in Solidity. Will panic! if 
@@ -674,6 +1919,44 @@ mod dao {
             self.proposals[prop_id as usize].clone()
         }
 
+        //NOTE: not a part of the original contract.
+        //Accounts that have voted on a proposal (at most `MAX_VOTERS_RETURNED`).
+        //Accounts stay in the list after un-voting.
+        #[ink(message)]
+        pub fn voters_of(&self, proposal_id: u64) -> Vec<AccountId> {
+            self.proposals[proposal_id as usize].voters
+                .iter()
+                .take(MAX_VOTERS_RETURNED)
+                .cloned()
+                .collect()
+        }
+
+        //NOTE: not a part of the original contract.
+        //The token balance `voter` cast their current vote with, or `None` if
+        //they have no vote on the proposal
+        #[ink(message)]
+        pub fn my_vote_weight(&self, proposal_id: u64, voter: AccountId) -> Option<u128> {
+            self.votes.get((proposal_id, voter))?;
+            self.vote_weights.get((proposal_id, voter))
+        }
+
+        //NOTE: not a part of the original contract.
+        //True while `voter` has a vote on an open proposal, i.e. the vote is part of
+        //the tally that decides the proposal. Withdrawn or pruned votes, and votes on
+        //closed proposals, do not count.
+        #[ink(message)]
+        pub fn is_vote_counted(&self, proposal_id: u64, voter: AccountId) -> bool {
+            self.proposals.get(proposal_id as usize).map_or(false, |p| p.open)
+                && self.votes.get((proposal_id, voter)).is_some()
+        }
+
+        //NOTE: not a part of the original contract.
+        //The current vote of `voter` on a proposal, `None` if they have not voted
+        #[ink(message)]
+        pub fn vote_of(&self, proposal_id: u64, voter: AccountId) -> Option<VotePosition> {
+            self.votes.get((proposal_id, voter))
+        }
+
         //NOTE: this function is for confirming the ERC20 cross-contract call
         //is working. It is not a part of the original contract
         #[ink(message)]
@@ -681,22 +1964,250 @@ mod dao {
             self.token.total_supply()
         }
 
-    }
+        //NOTE: not a part of the original contract.
+        //False if the DAO was instantiated with a neutral curator
+        #[ink(message)]
+        pub fn curator_can_vote(&self) -> bool {
+            !self.config.neutral_curator
+        }
 
-    //helper function for to hash the proposal
-    fn hash_proposal(recipient: &AccountId, amount: &Balance, transaction_data: &Vec<u8>) -> Hash {
-        let encodable = (recipient, amount, transaction_data); // Implements `scale::Encode`
-        let mut output = <Keccak256 as HashOutput>::Type::default(); // 256-bit buffer
-        ink_env::hash_encoded::<Keccak256, _>(&encodable, &mut output);
-        return Hash::from(output);
-    }
+        //NOTE: not a part of the original contract.
+        #[ink(message)]
+        pub fn decimals(&self) -> u8 {
+            self.config.decimals
+        }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        //NOTE: not a part of the original contract.
+        //Splits `amount` into its integer and fractional part according to `decimals`
+        #[ink(message)]
+        pub fn to_display_units(&self, amount: Balance) -> (u128, u128) {
+            let unit = 10u128.pow(self.config.decimals as u32);
+            (amount / unit, amount % unit)
+        }
 
-        use ink_env::Clear;
-        use ink_lang as ink;
+        //NOTE: not a part of the original contract.
+        //True once the treasury has been moved to a successor contract
+        #[ink(message)]
+        pub fn migrated(&self) -> bool {
+            self.migrated
+        }
+
+        //NOTE: not a part of the original contract.
+        //Sums the `releasable()` amount of VestingWallets that pay out to this DAO.
+        //Wallets whose call fails are skipped, so the result is a lower bound.
+        #[ink(message)]
+        pub fn total_unreleased_vesting(&self, wallets: Vec<AccountId>) -> Balance {
+            let mut total: Balance = 0;
+            for wallet in wallets {
+                if let Some(amount) = self.get_releasable(wallet) {
+                    total = total.saturating_add(amount);
+                }
+            }
+            total
+        }
+
+        //NOTE: not a part of the original contract.
+        //Address of the token used to weight votes
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token.to_account_id()
+        }
+
+        //NOTE: not a part of the original contract.
+        //Whether the proposal passed, `None` if it does not exist. The call of a passed
+        //proposal may still have failed, see `executed_proposals`.
+        #[ink(message)]
+        pub fn proposal_passed(&self, proposal_id: u64) -> Option<bool> {
+            if proposal_id == 0 { // index 0 is the null entry (see constructor)
+                return None;
+            }
+            self.proposals.get(proposal_id as usize).map(|p| p.proposal_passed)
+        }
+
+        //NOTE: not a part of the original contract.
+        //Ids of all proposals that passed and were executed
+        #[ink(message)]
+        pub fn executed_proposals(&self) -> Vec<u64> {
+            self.proposals.iter()
+                .enumerate()
+                .filter(|(_, p)| p.executed_at.is_some())
+                .map(|(id, _)| id as u64)
+                .collect()
+        }
+
+        //NOTE: not a part of the original contract.
+        //The encoding of `transaction_data` as the arguments of the call made by
+        //`execute_proposal`, which follow the 4 byte selector. The bytes are passed
+        //through unchanged: `transaction_data` must already be the SCALE encoded
+        //arguments, without a length prefix.
+        #[ink(message)]
+        pub fn encode_call_input(&self, transaction_data: Vec<u8>) -> Vec<u8> {
+            scale::Encode::encode(&CallInput(&transaction_data))
+        }
+
+        //NOTE: not a part of the original contract.
+        //The account of the DAO, e.g. the recipient of governance proposals
+        #[ink(message)]
+        pub fn contract_address(&self) -> AccountId {
+            self.env().account_id()
+        }
+
+        //NOTE: not a part of the original contract.
+        //Number of votes cast so far on all proposals, including changed votes
+        //on the same proposal
+        #[ink(message)]
+        pub fn vote_seq(&self) -> u64 {
+            self.vote_seq
+        }
+
+        //NOTE: not a part of the original contract.
+        //Number of proposals created, passed, failed and cancelled
+        #[ink(message)]
+        pub fn stats(&self) -> DaoStats {
+            self.stats.clone()
+        }
+
+        //NOTE: not a part of the original contract.
+        //The latest times an executed proposal reached quorum, oldest first.
+        //`last_time_min_quorum_met` is only the most recent of them.
+        #[ink(message)]
+        pub fn quorum_met_history(&self) -> Vec<Timestamp> {
+            self.quorum_met_history.clone()
+        }
+
+        fn record_quorum_met(&mut self, now: Timestamp) {
+            if self.quorum_met_history.len() == MAX_QUORUM_MET_HISTORY {
+                self.quorum_met_history.remove(0);
+            }
+            self.quorum_met_history.push(now);
+        }
+
+    }
+
+    // Token balances used instead of the ERC20 contract when running tests,
+    // since cross-contract calls are not supported off-chain.
+    // Accounts without an entry hold 1 token.
+    #[cfg(test)]
+    thread_local! {
+        static TEST_TOKEN_BALANCES: core::cell::RefCell<ink_prelude::collections::BTreeMap<AccountId, Balance>> = core::cell::RefCell::new(ink_prelude::collections::BTreeMap::new());
+        static TEST_TOKEN_TOTAL_SUPPLY: core::cell::Cell<Balance> = core::cell::Cell::new(7);
+    }
+
+    // Moves tokens between the test balances, like the ERC20 `transfer` and
+    // `transfer_from` (allowances are not checked). Returns false if `from`
+    // does not hold `amount`.
+    #[cfg(test)]
+    fn move_test_tokens(from: AccountId, to: AccountId, amount: Balance) -> bool {
+        TEST_TOKEN_BALANCES.with(|balances| {
+            let mut balances = balances.borrow_mut();
+            let from_balance = *balances.get(&from).unwrap_or(&1);
+            if from_balance < amount {
+                return false
+            }
+            let to_balance = *balances.get(&to).unwrap_or(&1);
+            balances.insert(from, from_balance - amount);
+            balances.insert(to, to_balance + amount);
+            true
+        })
+    }
+
+    // The `releasable()` amounts of the VestingWallets queried by `total_unreleased_vesting`
+    // when running tests. The call of a wallet without an entry fails.
+    #[cfg(test)]
+    thread_local! {
+        static TEST_RELEASABLE: core::cell::RefCell<ink_prelude::collections::BTreeMap<AccountId, Balance>> = core::cell::RefCell::new(ink_prelude::collections::BTreeMap::new());
+    }
+
+    // Results of the calls made by `execute_proposal` when running tests, by recipient,
+    // since cross-contract calls are not supported off-chain. Calls to recipients
+    // without an entry are made and panic. `TEST_CALLS` records the stubbed calls.
+    #[cfg(test)]
+    thread_local! {
+        static TEST_CALL_RESULTS: core::cell::RefCell<ink_prelude::collections::BTreeMap<AccountId, bool>> = core::cell::RefCell::new(ink_prelude::collections::BTreeMap::new());
+        static TEST_CALLS: core::cell::RefCell<Vec<(AccountId, [u8; 4], Balance)>> = core::cell::RefCell::new(Vec::new());
+    }
+
+    // The result of a call of `recipient` stubbed by the tests, `None` if it is not stubbed
+    #[cfg(test)]
+    fn stubbed_call(recipient: AccountId, function_selector: [u8; 4], amount: Balance) -> Option<Result<()>> {
+        let succeeds = TEST_CALL_RESULTS.with(|results| results.borrow().get(&recipient).copied())?;
+        TEST_CALLS.with(|calls| calls.borrow_mut().push((recipient, function_selector, amount)));
+        if succeeds {
+            Some(Ok(()))
+        } else {
+            Some(Err(Error::TransactionFailed))
+        }
+    }
+
+    //true if the transaction data calls `newContract`, which moves the whole treasury
+    fn is_high_quorum_call(transaction_data: &[u8]) -> bool {
+        transaction_data.starts_with(&NEW_CONTRACT_SELECTOR)
+    }
+
+    //true if the proposal moves the whole treasury, like a call of `newContract`,
+    //or replaces the code that guards it
+    fn is_high_quorum_kind(kind: &ProposalKind) -> bool {
+        matches!(kind, ProposalKind::Migrate { .. } | ProposalKind::SetCodeHash { .. })
+    }
+
+    //helper function for to hash the proposal
+    fn hash_proposal(recipient: &AccountId, amount: &Balance, transaction_data: &Vec<u8>) -> Hash {
+        let encodable = (recipient, amount, transaction_data); // Implements `scale::Encode`
+        let mut output = <Keccak256 as HashOutput>::Type::default(); // 256-bit buffer
+        ink_env::hash_encoded::<Keccak256, _>(&encodable, &mut output);
+        return Hash::from(output);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_env::Clear;
+        use ink_lang as ink;
+
+        type Env = ink_env::DefaultEnvironment;
+
+        fn set_token_balance(account: AccountId, balance: Balance) {
+            TEST_TOKEN_BALANCES.with(|balances| balances.borrow_mut().insert(account, balance));
+        }
+
+        fn set_token_total_supply(total_supply: Balance) {
+            TEST_TOKEN_TOTAL_SUPPLY.with(|cell| cell.set(total_supply));
+        }
+
+        fn balance_of(account: AccountId) -> Balance {
+            ink_env::test::get_account_balance::<Env>(account).unwrap()
+        }
+
+        //calls of `recipient` succeed or fail instead of panicking
+        fn stub_calls_to(recipient: AccountId, succeeds: bool) {
+            TEST_CALL_RESULTS.with(|results| results.borrow_mut().insert(recipient, succeeds));
+        }
+
+        fn stubbed_calls() -> Vec<(AccountId, [u8; 4], Balance)> {
+            TEST_CALLS.with(|calls| calls.borrow().clone())
+        }
+
+        //advance blocks until the off-chain timestamp reaches `timestamp`
+        fn advance_to(timestamp: Timestamp) {
+            while ink_env::block_timestamp::<Env>() < timestamp {
+                ink_env::test::advance_block::<Env>();
+            }
+        }
+
+        //creates a governance proposal as bob, votes for it and moves past its deadline
+        fn pass_governance_proposal(dao: &mut Dao, kind: ProposalKind) -> u64 {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            let proposal_id = dao.new_governance_proposal(kind, Vec::<u8>::from("gov"), 2 * WEEK).unwrap();
+
+            dao.vote(proposal_id, true).unwrap();
+            dao.refresh_pre_support(proposal_id);
+
+            advance_to(dao.proposals[proposal_id as usize].voting_deadline);
+            proposal_id
+        }
 
         /// The default constructor does its job.
         #[ink::test]
@@ -718,6 +2229,27 @@ mod dao {
             //TODO: assert_eq!(dao.allowed_recipients.get(<contract address>).unwrap(), true)
         }
 
+        #[ink::test]
+        fn parameters_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { partial_refund_bps: 5_000, neutral_curator: true, ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 3, AccountId::from([0x01; 32]), config.clone());
+            dao.set_proposal_cooldown(DAY).unwrap();
+
+            assert_eq!(dao.parameters(), DaoParameters {
+                proposal_deposit: 3,
+                min_quorum_divisor: 7,
+                min_proposal_debate_period: 2 * WEEK,
+                max_proposal_debate_period: 8 * WEEK,
+                execute_proposal_period: 10 * DAY,
+                pre_support_time: 2 * DAY,
+                quorum_halving_period: 25 * WEEK,
+                proposal_cooldown: DAY,
+                max_execution_gas: 0,
+                config,
+            });
+        }
+
         #[ink::test]
         fn new_proposal_works(){
             let accounts =
@@ -739,6 +2271,161 @@ mod dao {
             //TODO: check all fields -- if worth the time
         }
 
+        #[ink::test]
+        fn validate_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            dao.set_proposal_cooldown(DAY).unwrap();
+
+            //the contract itself can not propose (the off-chain callee is alice)
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, 2 * WEEK), Err(Error::ProposalCreationFailed));
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, 2 * WEEK), Ok(()));
+            assert_eq!(dao.validate_proposal(accounts.eve, 5, 2 * WEEK), Err(Error::ProposalCreationFailed));
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, WEEK), Err(Error::ProposalCreationFailed));
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, 9 * WEEK), Err(Error::ProposalCreationFailed));
+
+            //no proposal is created and no deposit is needed
+            assert_eq!(dao.number_of_proposals(), 0);
+
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, 2 * WEEK), Err(Error::TooSoon));
+
+            set_token_balance(accounts.charlie, 0);
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, 2 * WEEK), Err(Error::NotTokenholder));
+
+            dao.migrated = true;
+            assert_eq!(dao.validate_proposal(accounts.alice, 5, 2 * WEEK), Err(Error::DaoMigrated));
+        }
+
+        #[ink::test]
+        fn resubmit_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            //an open proposal can not be resubmitted
+            assert_eq!(dao.resubmit_proposal(1, vec![0x02; 5], 3 * WEEK), Err(Error::ProposalCreationFailed));
+
+            //nobody votes, the proposal fails
+            advance_to(dao.proposals[1].voting_deadline);
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(dao.proposals[1].proposal_passed, false);
+
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(dao.resubmit_proposal(1, vec![0x02; 5], 3 * WEEK), Err(Error::NotCreator));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.resubmit_proposal(1, vec![0x03; 5], 3 * WEEK), Err(Error::ProposalCreationFailed));
+
+            let now = ink_env::block_timestamp::<Env>();
+            assert_eq!(dao.resubmit_proposal(1, vec![0x02; 5], 3 * WEEK), Ok(2));
+            let (old, new) = (&dao.proposals[1], &dao.proposals[2]);
+            assert_eq!(new.recipient, old.recipient);
+            assert_eq!(new.amount, old.amount);
+            assert_eq!(new.description, old.description);
+            assert_eq!(new.proposal_hash, old.proposal_hash);
+            assert_eq!(new.creator, accounts.bob);
+            assert_eq!(new.voting_deadline, now + 3 * WEEK);
+            assert_eq!(new.open, true);
+            assert_eq!(new.yea, 0);
+        }
+
+        #[ink::test]
+        fn duplicate_proposals_rejected_when_configured(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { reject_duplicate_proposals: true, ..Default::default() };
+            let mut dedup = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+
+            assert_eq!(dedup.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Ok(1));
+            //the description is not part of the proposal hash
+            assert_eq!(dedup.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK), Err(Error::ProposalCreationFailed));
+            assert_eq!(dedup.new_proposal(accounts.alice, 6, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK), Ok(2));
+
+            //once closed, the proposal can be made again
+            dedup.close_proposal(1);
+            assert_eq!(dedup.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 3"), vec![0x02; 5], 2 * WEEK), Ok(3));
+
+            //duplicates are allowed by default
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Ok(1));
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK), Ok(2));
+        }
+
+        #[ink::test]
+        fn max_execution_gas_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            //no limit by default
+            assert_eq!(dao.execution_gas_limit(0), 0);
+            assert_eq!(dao.execution_gas_limit(u64::MAX), u64::MAX);
+
+            assert_eq!(dao.set_max_execution_gas(1_000), Ok(()));
+            assert_eq!(dao.max_execution_gas(), 1_000);
+            assert_eq!(dao.execution_gas_limit(500), 500);
+            assert_eq!(dao.execution_gas_limit(1_001), 1_000);
+            //0 would forward all remaining gas
+            assert_eq!(dao.execution_gas_limit(0), 1_000);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.set_max_execution_gas(0), Err(Error::NotCurator));
+            assert_eq!(dao.max_execution_gas(), 1_000);
+        }
+
+        #[ink::test]
+        fn proposal_cooldown_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.set_proposal_cooldown(DAY), Ok(()));
+            assert_eq!(dao.proposal_cooldown(), DAY);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.set_proposal_cooldown(0), Err(Error::NotCurator));
+            ink_env::test::set_value_transferred::<Env>(2);
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Ok(1));
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK), Err(Error::TooSoon));
+
+            //other creators are not affected
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK), Ok(2));
+
+            advance_to(DAY);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 3"), vec![0x02; 5], 2 * WEEK), Ok(3));
+        }
+
+        #[ink::test]
+        fn recipient_min_deposit_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            dao.change_allowed_recipients(accounts.eve, true).unwrap();
+            assert_eq!(dao.set_recipient_min_deposit(accounts.eve, 10), Ok(()));
+            assert_eq!(dao.recipient_min_deposit(accounts.eve), Some(10));
+            assert_eq!(dao.recipient_min_deposit(accounts.alice), None);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.set_recipient_min_deposit(accounts.eve, 0), Err(Error::NotCurator));
+
+            ink_env::test::set_value_transferred::<Env>(2);
+            assert_eq!(dao.new_proposal(accounts.eve, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Err(Error::ProposalCreationFailed));
+            //other recipients use the base deposit
+            assert_eq!(dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Ok(1));
+
+            ink_env::test::set_value_transferred::<Env>(10);
+            assert_eq!(dao.new_proposal(accounts.eve, 5, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK), Ok(2));
+
+            //the override is removed with 0
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.set_recipient_min_deposit(accounts.eve, 0), Ok(()));
+            assert_eq!(dao.recipient_min_deposit(accounts.eve), None);
+        }
+
         #[ink::test]
         fn check_proposal_code_works(){ 
             let accounts =
@@ -766,16 +2453,341 @@ mod dao {
             let transaction_data = vec![0x02; 5];
             dao.new_proposal(recipient, amount, Vec::<u8>::from("prop 1"), transaction_data.clone(), 2 * WEEK).unwrap();
 
-            dao.vote(1, true);
+            dao.vote(1, true).unwrap();
             ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
-            dao.vote(1, false);
+            dao.vote(1, false).unwrap();
 
             let p = &dao.proposals[1];
 
             assert_eq!(p.yea, 1);
             assert_eq!(p.nay, 1);
-            assert_eq!(*p.voted_yes.get(&accounts.bob).unwrap(), true);
-            assert_eq!(*p.voted_no.get(&accounts.charlie).unwrap(), true);
+            assert_eq!(dao.vote_of(1, accounts.bob), Some(VotePosition::Yes));
+            assert_eq!(dao.vote_of(1, accounts.charlie), Some(VotePosition::No));
+        }
+
+        #[ink::test]
+        fn neutral_curator_can_not_vote(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { neutral_curator: true, ..Default::default() };
+            let mut neutral = Dao::new_with_config(accounts.django, 1, AccountId::from([0x01; 32]), config);
+            let mut dao = Dao::new(accounts.django, 1, AccountId::from([0x01; 32]));
+            assert_eq!(neutral.curator_can_vote(), false);
+            assert_eq!(dao.curator_can_vote(), true);
+
+            for dao in [&mut neutral, &mut dao] {
+                ink_env::test::set_caller::<Env>(accounts.bob);
+                ink_env::test::set_value_transferred::<Env>(2);
+                dao.new_proposal(accounts.django, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+                ink_env::test::set_caller::<Env>(accounts.django);
+            }
+
+            assert_eq!(neutral.vote(1, true), Err(Error::CallerIsCurator));
+            assert_eq!(neutral.proposals[1].yea, 0);
+            assert_eq!(dao.vote(1, true), Ok(()));
+            assert_eq!(dao.proposals[1].yea, 1);
+
+            //other accounts vote as usual
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(neutral.vote(1, true), Ok(()));
+        }
+
+        #[ink::test]
+        fn voters_of_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            dao.vote(1, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.django);
+            dao.vote(1, true).unwrap();
+            //switching the vote does not add bob a second time
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.vote(1, false).unwrap();
+
+            assert_eq!(dao.voters_of(1), vec![accounts.bob, accounts.charlie, accounts.django]);
+        }
+
+        #[ink::test]
+        fn my_vote_weight_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            assert_eq!(dao.my_vote_weight(1, accounts.bob), None);
+
+            set_token_balance(accounts.bob, 40);
+            dao.vote(1, true).unwrap();
+            //bob transfers most of his tokens after voting
+            set_token_balance(accounts.bob, 10);
+            assert_eq!(dao.my_vote_weight(1, accounts.bob), Some(40));
+
+            //un-voting removes exactly the weight the vote was cast with
+            dao.un_vote(1).unwrap();
+            assert_eq!(dao.proposals[1].yea, 0);
+            assert_eq!(dao.my_vote_weight(1, accounts.bob), None);
+
+            //voting again uses the current balance
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.my_vote_weight(1, accounts.bob), Some(10));
+            assert_eq!(dao.proposals[1].nay, 10);
+        }
+
+        #[ink::test]
+        fn prune_vote_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            set_token_balance(accounts.bob, 5);
+            dao.vote(1, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.proposals[1].yea, 6);
+
+            //bob still holds tokens
+            assert_eq!(dao.prune_vote(1, accounts.bob), Err(Error::VoterHoldsTokens));
+
+            //bob sells all tokens, anyone can prune the vote
+            set_token_balance(accounts.bob, 0);
+            assert_eq!(dao.prune_vote(1, accounts.bob), Ok(()));
+            let p = &dao.proposals[1];
+            assert_eq!(p.yea, 1);
+            assert_eq!(dao.vote_of(1, accounts.bob), None);
+            assert_eq!(dao.vote_of(1, accounts.charlie), Some(VotePosition::Yes));
+        }
+
+        #[ink::test]
+        fn vote_margin_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            set_token_balance(accounts.bob, 5);
+            set_token_balance(accounts.charlie, 3);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.vote_margin(1), Some((0, false)));
+            assert_eq!(dao.vote_margin(2), None);
+
+            //yea leading
+            dao.vote(1, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.vote_margin(1), Some((2, true)));
+
+            //nay leading
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.vote_margin(1), Some((-8, false)));
+
+            //tied
+            set_token_balance(accounts.bob, 3);
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.vote_margin(1), Some((0, false)));
+        }
+
+        #[ink::test]
+        fn vote_margin_does_not_overflow(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            set_token_balance(accounts.bob, u128::MAX);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.vote_margin(1), None);
+        }
+
+        #[ink::test]
+        fn treasury_impact_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 1000);
+            dao.change_allowed_recipients(accounts.django, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            let spend = dao.new_proposal(accounts.django, 300, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            let change_token = dao.new_governance_proposal(ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) }, Vec::new(), 2 * WEEK).unwrap();
+
+            assert_eq!(dao.treasury_impact(spend), -300);
+            //zero-amount proposals only refund their deposit
+            assert_eq!(dao.treasury_impact(change_token), 0);
+        }
+
+        #[ink::test]
+        fn yea_needed_to_pass_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            //a quorum of 10 tokens for proposals without an amount
+            set_token_total_supply(70);
+            set_token_balance(accounts.bob, 3);
+            set_token_balance(accounts.charlie, 20);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.yea_needed_to_pass(1), 10);
+
+            //short of quorum
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.yea_needed_to_pass(1), 7);
+
+            //past quorum, but behind on votes
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.yea_needed_to_pass(1), 18);
+
+            //already passing
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.yea_needed_to_pass(1), 0);
+        }
+
+        #[ink::test]
+        fn is_decided_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { reject_votes_once_decided: true, ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            set_token_total_supply(10);
+            set_token_balance(accounts.bob, 5);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.is_decided(1), false);
+
+            //the 5 tokens that have not voted could still tie the vote
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.is_decided(1), false);
+
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.is_decided(1), true);
+
+            ink_env::test::set_caller::<Env>(accounts.django);
+            assert_eq!(dao.vote(1, false), Err(Error::ProposalDecided));
+            assert_eq!(dao.proposals[1].nay, 0);
+        }
+
+        #[ink::test]
+        fn default_dao_accepts_votes_once_decided(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            set_token_total_supply(10);
+            set_token_balance(accounts.bob, 5);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.is_decided(1), true);
+
+            ink_env::test::set_caller::<Env>(accounts.django);
+            assert_eq!(dao.vote(1, false), Ok(()));
+            assert_eq!(dao.proposals[1].nay, 1);
+        }
+
+        #[ink::test]
+        fn repeated_vote_is_rejected(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            set_token_balance(accounts.bob, 3);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            assert_eq!(dao.vote(1, true), Ok(()));
+            assert_eq!(dao.vote(1, true), Err(Error::NoChange));
+            assert_eq!(dao.proposals[1].yea, 3);
+            assert_eq!(dao.voting_register.get(accounts.bob), Some(vec![1]));
+
+            //a changed balance updates the weight
+            set_token_balance(accounts.bob, 5);
+            assert_eq!(dao.vote(1, true), Ok(()));
+            assert_eq!(dao.proposals[1].yea, 5);
+
+            //after the deadline the vote is too late, not unchanged
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.vote(1, true), Err(Error::OutsideDeadline));
+        }
+
+        #[ink::test]
+        fn revote_cooldown_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { revote_cooldown: HOUR, ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            //changing a vote is not affected
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.vote(1, false), Ok(()));
+
+            dao.un_vote(1).unwrap();
+            let unvoted_at = ink_env::block_timestamp::<Env>();
+            assert_eq!(dao.vote(1, true), Err(Error::TooSoon));
+            assert_eq!(dao.proposals[1].yea, 0);
+
+            advance_to(unvoted_at + HOUR);
+            assert_eq!(dao.vote(1, true), Ok(()));
+            assert_eq!(dao.proposals[1].yea, 1);
+        }
+
+        #[ink::test]
+        fn is_vote_counted_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.is_vote_counted(1, accounts.bob), false);
+
+            dao.vote(1, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.is_vote_counted(1, accounts.bob), true);
+            assert_eq!(dao.is_vote_counted(1, accounts.charlie), true);
+
+            //pruned
+            set_token_balance(accounts.bob, 0);
+            dao.prune_vote(1, accounts.bob).unwrap();
+            assert_eq!(dao.is_vote_counted(1, accounts.bob), false);
+
+            //closed
+            advance_to(dao.proposals[1].voting_deadline);
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(dao.vote_of(1, accounts.charlie), Some(VotePosition::Yes));
+            assert_eq!(dao.is_vote_counted(1, accounts.charlie), false);
+            assert_eq!(dao.is_vote_counted(7, accounts.charlie), false);
+        }
+
+        #[ink::test]
+        fn changing_a_vote_moves_its_weight(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            set_token_balance(accounts.bob, 3);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.vote_of(1, accounts.bob), None);
+
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.vote_of(1, accounts.bob), Some(VotePosition::Yes));
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.vote_of(1, accounts.bob), Some(VotePosition::No));
+
+            let p = dao.get_proposal(1);
+            assert_eq!(p.yea, 0);
+            assert_eq!(p.nay, 3);
         }
 
         #[ink::test]
@@ -790,9 +2802,9 @@ mod dao {
             let transaction_data = vec![0x02; 5];
             dao.new_proposal(recipient, amount, Vec::<u8>::from("prop 1"), transaction_data.clone(), 2 * WEEK).unwrap();
 
-            dao.vote(1, true);
+            dao.vote(1, true).unwrap();
             ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
-            dao.vote(1, false);
+            dao.vote(1, false).unwrap();
 
             dao.un_vote(1);
             ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
@@ -801,8 +2813,8 @@ mod dao {
             let p = &dao.proposals[1];
             assert_eq!(p.yea, 0);
             assert_eq!(p.nay, 0);
-            assert_eq!(*p.voted_yes.get(&accounts.bob).unwrap(), false);
-            assert_eq!(*p.voted_no.get(&accounts.charlie).unwrap(), false);
+            assert_eq!(dao.vote_of(1, accounts.bob), None);
+            assert_eq!(dao.vote_of(1, accounts.charlie), None);
         }
 
         #[ink::test]
@@ -821,8 +2833,8 @@ mod dao {
 
             ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
 
-            dao.vote(1, true);
-            dao.vote(2, true);
+            dao.vote(1, true).unwrap();
+            dao.vote(2, true).unwrap();
 
             dao.un_vote_all();
 
@@ -832,8 +2844,8 @@ mod dao {
             assert_eq!(p1.nay, 0);
             assert_eq!(p2.yea, 0);
             assert_eq!(p2.nay, 0);
-            assert_eq!(*p1.voted_yes.get(&accounts.bob).unwrap(), false);
-            assert_eq!(*p2.voted_yes.get(&accounts.bob).unwrap(), false);
+            assert_eq!(dao.vote_of(1, accounts.bob), None);
+            assert_eq!(dao.vote_of(2, accounts.bob), None);
         }
 
         #[ink::test]
@@ -849,12 +2861,12 @@ mod dao {
             let transaction_data = vec![0x02; 5];
             dao.new_proposal(recipient, amount, Vec::<u8>::from("prop 1"), transaction_data.clone(), 2 * WEEK).unwrap();
             
-            dao.vote(1, true);
+            dao.vote(1, true).unwrap();
             ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
-            dao.vote(1, true);
+            dao.vote(1, true).unwrap();
 
-            //verify pre_support before increasing timestamp
-            dao.verify_pre_support(1);
+            //refresh pre_support before increasing timestamp
+            dao.refresh_pre_support(1);
 
             //increase timestamp
             for _ in 0..300000{
@@ -885,6 +2897,1264 @@ mod dao {
             assert_eq!(dao.sum_of_proposal_deposits, 0);
         }
 
+        #[ink::test]
+        fn change_token_proposal_works(){
+            let mut dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            let new_token = AccountId::from([0x09; 32]);
+            assert_eq!(dao.new_governance_proposal(ProposalKind::Transaction, Vec::new(), 2 * WEEK), Err(Error::ProposalCreationFailed));
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token });
+
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+            let p = &dao.proposals[proposal_id as usize];
+            assert_eq!(p.proposal_passed, true);
+            assert_eq!(p.open, false);
+            //votes are now weighted by the new token
+            assert_eq!(dao.token(), new_token);
+        }
+
+        #[ink::test]
+        fn change_token_blocked_while_proposals_open(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 1, Vec::<u8>::from("prop 1"), vec![0x02; 5], 3 * WEEK).unwrap();
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) });
+
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Err(Error::ProposalsStillOpen));
+            assert_eq!(dao.token(), AccountId::from([0x01; 32]));
+            assert_eq!(dao.proposals[proposal_id as usize].open, true);
+        }
+
+        #[ink::test]
+        fn change_allowed_recipient_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.allowed_recipients.get(accounts.django).unwrap_or(false), false);
+
+            let kind = ProposalKind::ChangeAllowedRecipient { recipient: accounts.django, allowed: true };
+            let proposal_id = pass_governance_proposal(&mut dao, kind);
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+            assert_eq!(dao.proposals[proposal_id as usize].proposal_passed, true);
+            //no curator involved
+            assert_eq!(dao.allowed_recipients.get(accounts.django), Some(true));
+        }
+
+        #[ink::test]
+        fn removing_a_recipient_waits_for_its_open_proposals(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            dao.change_allowed_recipients(accounts.django, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.django, 1, Vec::<u8>::from("prop 1"), vec![0x02; 5], 3 * WEEK).unwrap();
+
+            let kind = ProposalKind::ChangeAllowedRecipient { recipient: accounts.django, allowed: false };
+            let proposal_id = pass_governance_proposal(&mut dao, kind);
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Err(Error::ProposalsStillOpen));
+            assert_eq!(dao.allowed_recipients.get(accounts.django), Some(true));
+            assert_eq!(dao.proposals[proposal_id as usize].open, true);
+        }
+
+        #[ink::test]
+        fn code_upgrade_needs_a_passed_proposal(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            let kind = ProposalKind::SetCodeHash { code_hash: Hash::from([0x02; 32]) };
+            let proposal_id = dao.new_governance_proposal(kind.clone(), Vec::<u8>::from("upgrade"), 2 * WEEK).unwrap();
+            assert_eq!(dao.proposals[proposal_id as usize].kind, kind);
+
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Err(Error::ProposalExecutionFailed));
+
+            //without support the proposal is closed without upgrading
+            advance_to(dao.proposals[proposal_id as usize].voting_deadline);
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+            assert_eq!(dao.proposals[proposal_id as usize].proposal_passed, false);
+            assert_eq!(dao.proposals[proposal_id as usize].open, false);
+        }
+
+        #[ink::test]
+        fn code_upgrade_needs_the_treasury_quorum(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 1000);
+            let kind = ProposalKind::SetCodeHash { code_hash: Hash::from([0x02; 32]) };
+
+            //bob's single token only meets the base quorum
+            let proposal_id = pass_governance_proposal(&mut dao, kind);
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+            assert_eq!(dao.proposals[proposal_id as usize].proposal_passed, false);
+            assert_eq!(dao.proposals[proposal_id as usize].open, false);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "set_code_hash")]
+        fn passed_code_upgrade_sets_the_code_hash(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 1000);
+            set_token_balance(accounts.bob, 3);
+            let kind = ProposalKind::SetCodeHash { code_hash: Hash::from([0x02; 32]) };
+
+            //the off-chain environment can not replace the code
+            let proposal_id = pass_governance_proposal(&mut dao, kind);
+            let _ = dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0);
+        }
+
+        #[ink::test]
+        fn first_proposal_ends_fueling_by_default(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.is_fueled(), false);
+            advance_to(QUORUM_HALVING_PERIOD);
+
+            //the quorum can not be halved before the first proposal
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.halve_min_quorum(), Err(Error::UnableToHalveQuorum));
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            let first_at = ink_env::block_timestamp::<Env>();
+            assert_eq!(dao.is_fueled(), true);
+            assert_eq!(dao.last_time_min_quorum_met, first_at);
+
+            //later proposals leave the quorum timing alone
+            advance_to(first_at + DAY);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.last_time_min_quorum_met, first_at);
+
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.close_fueling(), Err(Error::NoChange));
+            advance_to(first_at + MIN_PROPOSAL_DEBATE_PERIOD + 1);
+            assert_eq!(dao.halve_min_quorum(), Ok(()));
+        }
+
+        #[ink::test]
+        fn explicit_fueling_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { explicit_fueling: true, ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            assert_eq!(dao.is_fueled(), false);
+            advance_to(QUORUM_HALVING_PERIOD);
+
+            //every proposal during fueling restarts the quorum timing
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.last_time_min_quorum_met, ink_env::block_timestamp::<Env>());
+            advance_to(QUORUM_HALVING_PERIOD + DAY);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.last_time_min_quorum_met, QUORUM_HALVING_PERIOD + DAY);
+
+            //the quorum can not be halved before fueling is closed
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            advance_to(dao.last_time_min_quorum_met + MIN_PROPOSAL_DEBATE_PERIOD + 1);
+            assert_eq!(dao.halve_min_quorum(), Err(Error::UnableToHalveQuorum));
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.close_fueling(), Err(Error::NotCurator));
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.close_fueling(), Ok(()));
+            assert_eq!(dao.close_fueling(), Err(Error::NoChange));
+            assert_eq!(dao.is_fueled(), true);
+            let closed_at = ink_env::block_timestamp::<Env>();
+            assert_eq!(dao.last_time_min_quorum_met, closed_at);
+
+            //later proposals leave the quorum timing alone
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            advance_to(closed_at + DAY);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 3"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.last_time_min_quorum_met, closed_at);
+
+            //and the curator can halve the quorum after the usual delay
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.halve_min_quorum(), Err(Error::UnableToHalveQuorum));
+            advance_to(closed_at + MIN_PROPOSAL_DEBATE_PERIOD + 1);
+            assert_eq!(dao.halve_min_quorum(), Ok(()));
+            assert_eq!(dao.min_quorum_divisor, 14);
+        }
+
+        #[ink::test]
+        fn reset_quorum_divisor_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            //the curator halves the quorum twice
+            //(`halve_min_quorum` subtracts the halving period from the current time)
+            advance_to(QUORUM_HALVING_PERIOD);
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            for _ in 0..2 {
+                advance_to(dao.last_time_min_quorum_met + MIN_PROPOSAL_DEBATE_PERIOD + 1);
+                dao.halve_min_quorum().unwrap();
+            }
+            assert_eq!(dao.min_quorum_divisor, 28);
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ResetQuorumDivisor);
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+            assert_eq!(dao.min_quorum_divisor, 7);
+            assert_eq!(dao.last_time_min_quorum_met, ink_env::block_timestamp::<Env>());
+        }
+
+        #[ink::test]
+        fn migrate_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 1000);
+            dao.change_allowed_recipients(accounts.django, true).unwrap();
+            let django_before = ink_env::test::get_account_balance::<Env>(accounts.django).unwrap();
+            //the quorum for spending the whole treasury
+            set_token_balance(accounts.bob, 3);
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::Migrate { successor: accounts.django });
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+
+            //the treasury (minus bob's refunded deposit) moved to the successor
+            assert_eq!(dao.migrated(), true);
+            assert_eq!(ink_env::test::get_account_balance::<Env>(accounts.django).unwrap(), django_before + 998);
+            assert_eq!(ink_env::test::get_account_balance::<Env>(ink_env::test::callee::<Env>()).unwrap(), 0);
+
+            //the old DAO rejects further activity
+            ink_env::test::set_value_transferred::<Env>(2);
+            assert_eq!(dao.new_proposal(accounts.alice, 1, Vec::<u8>::from("prop"), vec![0x02; 5], 2 * WEEK), Err(Error::DaoMigrated));
+            assert_eq!(dao.vote(proposal_id, true), Err(Error::DaoMigrated));
+        }
+
+        #[ink::test]
+        fn migrate_needs_the_treasury_quorum(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 1000);
+            dao.change_allowed_recipients(accounts.django, true).unwrap();
+            let django_before = ink_env::test::get_account_balance::<Env>(accounts.django).unwrap();
+
+            //bob's single token meets the base quorum, but not the one of the treasury
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::Migrate { successor: accounts.django });
+            assert!(dao.proposals[proposal_id as usize].yea >= dao.min_quorum(0));
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+
+            assert_eq!(dao.proposals[proposal_id as usize].proposal_passed, false);
+            assert_eq!(dao.migrated(), false);
+            assert_eq!(ink_env::test::get_account_balance::<Env>(accounts.django).unwrap(), django_before);
+            assert_eq!(dao.actual_balance(), 998);
+        }
+
+        #[ink::test]
+        fn migrate_requires_allowed_successor(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+
+            assert_eq!(dao.new_governance_proposal(ProposalKind::Migrate { successor: accounts.django }, Vec::new(), 2 * WEEK), Err(Error::ProposalCreationFailed));
+        }
+
+        #[ink::test]
+        fn total_unreleased_vesting_without_wallets_is_zero(){
+            let dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.total_unreleased_vesting(Vec::new()), 0);
+        }
+
+        #[ink::test]
+        fn total_unreleased_vesting_sums_the_wallets(){
+            let dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            let (first, second, broken) = (AccountId::from([0x11; 32]), AccountId::from([0x12; 32]), AccountId::from([0x13; 32]));
+            TEST_RELEASABLE.with(|releasable| {
+                releasable.borrow_mut().insert(first, 300);
+                releasable.borrow_mut().insert(second, 500);
+            });
+
+            assert_eq!(dao.total_unreleased_vesting(vec![first, second]), 800);
+            //a wallet whose call fails is skipped
+            assert_eq!(dao.total_unreleased_vesting(vec![first, broken, second]), 800);
+            assert_eq!(dao.total_unreleased_vesting(vec![broken]), 0);
+
+            //the sum saturates instead of overflowing
+            TEST_RELEASABLE.with(|releasable| releasable.borrow_mut().insert(second, Balance::MAX));
+            assert_eq!(dao.total_unreleased_vesting(vec![first, second]), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn change_allowed_recipients_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+
+            assert_eq!(dao.change_allowed_recipients(accounts.bob, true), Ok(()));
+            assert_eq!(dao.allowed_recipients.get(accounts.bob).unwrap(), true);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.change_allowed_recipients(accounts.charlie, true), Err(Error::NotCurator));
+            assert_eq!(dao.allowed_recipients.get(accounts.charlie).unwrap_or(false), false);
+        }
+
+        #[ink::test]
+        fn fund_and_propose_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+
+            //an empty treasury receives exactly the deposit plus the amount
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 6);
+            ink_env::test::set_value_transferred::<Env>(6);
+            assert_eq!(dao.fund_and_propose(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Ok(1));
+
+            let p = &dao.proposals[1];
+            assert_eq!(p.proposal_deposit, 1);
+            assert_eq!(dao.sum_of_proposal_deposits, 1);
+            //the treasury can pay the proposal once it passes
+            assert_eq!(dao.actual_balance(), 5);
+            assert!(p.amount <= dao.actual_balance());
+        }
+
+        #[ink::test]
+        fn fund_and_propose_rejects_underfunding(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 5);
+            ink_env::test::set_value_transferred::<Env>(5);
+            assert_eq!(dao.fund_and_propose(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK), Err(Error::InsufficientBalance));
+            assert_eq!(dao.number_of_proposals(), 0);
+            assert_eq!(dao.sum_of_proposal_deposits, 0);
+        }
+
+        #[ink::test]
+        fn min_quorum_with_zero_divisor_does_not_panic(){
+            let mut dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.min_quorum(0), 1);
+
+            dao.min_quorum_divisor = 0;
+            assert_eq!(dao.min_quorum(0), u128::MAX);
+            assert_eq!(dao.min_quorum(5), u128::MAX);
+        }
+
+        #[ink::test]
+        fn quorum_percentage_bps_works(){
+            let mut dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.quorum_percentage_bps(), 1428);
+
+            //the divisor after one halving
+            dao.min_quorum_divisor = 14;
+            assert_eq!(dao.quorum_percentage_bps(), 714);
+
+            dao.min_quorum_divisor = 0;
+            assert_eq!(dao.quorum_percentage_bps(), u16::MAX);
+        }
+
+        #[ink::test]
+        fn zero_amount_quorum_ignores_empty_treasury(){
+            let dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 0);
+            assert_eq!(dao.min_quorum(0), 1);
+            //spending from an empty treasury can never reach quorum
+            assert_eq!(dao.min_quorum(5), u128::MAX);
+        }
+
+        //creates a proposal with `transaction_data` that bob supports with 1 token,
+        //while the treasury holds 98
+        fn high_quorum_setup(transaction_data: Vec<u8>) -> Dao {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 100);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), transaction_data, 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            dao.refresh_pre_support(1);
+            advance_to(dao.proposals[1].voting_deadline);
+            dao
+        }
+
+        #[ink::test]
+        fn new_contract_call_needs_treasury_quorum(){
+            assert!(is_high_quorum_call(&[0x68, 0x37, 0xff, 0x1e, 0x01]));
+            assert!(!is_high_quorum_call(&[0x68, 0x37, 0xff]));
+            assert!(!is_high_quorum_call(&[0x02; 5]));
+
+            //1 token meets the base quorum but not the quorum of spending the treasury
+            let transaction_data = vec![0x68, 0x37, 0xff, 0x1e, 0x01];
+            let mut dao = high_quorum_setup(transaction_data.clone());
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], transaction_data, 0), Ok(()));
+            assert_eq!(dao.proposals[1].proposal_passed, false);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "contract invocation")]
+        fn other_calls_need_base_quorum(){
+            //the proposal passes, and the off-chain environment can not invoke the call
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+        }
+
+        #[ink::test]
+        fn disallowed_selector_is_rejected(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.set_allowed_selectors(accounts.alice, vec![[0x01; 4]]), Ok(()));
+            assert_eq!(dao.allowed_selectors(accounts.alice), vec![[0x01; 4]]);
+
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Err(Error::ProposalExecutionFailed));
+            assert_eq!(dao.proposals[1].proposal_passed, false);
+            assert_eq!(dao.proposals[1].open, true);
+
+            //only the curator configures the list
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.set_allowed_selectors(accounts.alice, Vec::new()), Err(Error::NotCurator));
+        }
+
+        #[ink::test]
+        fn delisted_recipient_is_refunded_despite_a_disallowed_selector(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            dao.set_allowed_selectors(accounts.alice, vec![[0x01; 4]]).unwrap();
+            dao.change_allowed_recipients(accounts.alice, false).unwrap();
+
+            //the proposal is closed and the deposit refunded, nothing is called
+            let bob_before = balance_of(accounts.bob);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(dao.proposals[1].open, false);
+            assert_eq!(dao.proposals[1].proposal_passed, false);
+            assert_eq!(balance_of(accounts.bob), bob_before + 2);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "contract invocation")]
+        fn allowed_selector_is_called(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            dao.set_allowed_selectors(accounts.alice, vec![[0x01; 4], [0x00; 4]]).unwrap();
+
+            //passes the check, and the off-chain environment can not invoke the call
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+        }
+
+        #[ink::test]
+        fn pre_support_follows_majority(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.pre_support_status(1), false);
+            assert_eq!(dao.pre_support_status(2), false);
+
+            //a tie is no majority
+            dao.vote(1, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.refresh_pre_support(1), false);
+
+            ink_env::test::set_caller::<Env>(accounts.django);
+            dao.vote(1, true).unwrap();
+            //the status is current, the stored flag only after a refresh
+            assert_eq!(dao.pre_support_status(1), true);
+            assert_eq!(dao.proposals[1].pre_support, false);
+            assert_eq!(dao.refresh_pre_support(1), true);
+            assert_eq!(dao.proposals[1].pre_support, true);
+
+            //losing the majority before the pre support time withdraws the support
+            ink_env::test::set_caller::<Env>(accounts.eve);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.refresh_pre_support(1), false);
+            ink_env::test::set_caller::<Env>(accounts.frank);
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.refresh_pre_support(1), true);
+
+            //after that the recorded support is final
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+            ink_env::test::set_caller::<Env>(accounts.eve);
+            dao.un_vote(1).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.django);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.pre_support_status(1), true);
+            assert_eq!(dao.refresh_pre_support(1), true);
+        }
+
+        #[ink::test]
+        fn pre_support_is_final_without_refresh(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+
+            //nobody recorded the support in time, so executing refreshes to no support
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(dao.proposals[1].proposal_passed, false);
+            assert_eq!(dao.proposals[1].open, false);
+        }
+
+        #[ink::test]
+        fn drained_treasury_is_reported(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 100);
+            set_token_balance(accounts.bob, 7);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            dao.refresh_pre_support(1);
+
+            //the treasury shrinks to 2 (plus bob's deposit) before the deadline
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 4);
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Err(Error::InsufficientBalance));
+            assert_eq!(dao.proposals[1].open, true);
+            assert_eq!(dao.proposals[1].proposal_passed, false);
+
+            //a proposal that is not executed in time is closed as before
+            advance_to(dao.proposals[1].voting_deadline + EXECUTE_PROPOSAL_PERIOD + 1);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(dao.proposals[1].open, false);
+        }
+
+        #[ink::test]
+        fn empty_treasury_is_reported(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 100);
+            set_token_balance(accounts.bob, 7);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            dao.refresh_pre_support(1);
+
+            //only bob's deposit is left, no quorum can be reached for the amount
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 2);
+            assert_eq!(dao.actual_balance(), 0);
+            assert_eq!(dao.min_quorum(5), u128::MAX);
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Err(Error::InsufficientBalance));
+            assert_eq!(dao.proposals[1].open, true);
+            assert_eq!(dao.proposals[1].deposit_state, DepositState::Locked);
+        }
+
+        #[ink::test]
+        fn heavily_drained_treasury_is_reported(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 100);
+            set_token_balance(accounts.bob, 7);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 5, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            dao.refresh_pre_support(1);
+
+            //the treasury shrinks to 1, which raises the quorum for the amount above bob's 7 votes
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 3);
+            assert!(dao.min_quorum(5) > dao.proposals[1].yea);
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Err(Error::InsufficientBalance));
+            assert_eq!(dao.proposals[1].open, true);
+            assert_eq!(dao.proposals[1].deposit_state, DepositState::Locked);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "contract invocation")]
+        fn zero_amount_proposal_executes_with_empty_treasury(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            //the treasury only holds bob's deposit
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("set value"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            dao.refresh_pre_support(1);
+
+            advance_to(dao.proposals[1].voting_deadline);
+            //passes every check and reaches the call to the recipient, which
+            //panics because contract invocation is not supported off-chain
+            let _ = dao.execute_proposal(1, vec![1, 2, 3, 4], vec![0x02; 5], 1000);
+        }
+
+        #[ink::test]
+        fn audit_deposits_reports_drift(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(5);
+            dao.new_proposal(accounts.alice, 1, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            ink_env::test::set_value_transferred::<Env>(3);
+            dao.new_proposal(accounts.alice, 1, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.close_proposal(1);
+            assert_eq!(dao.audit_deposits(), (3, 3));
+
+            //inject drift
+            dao.sum_of_proposal_deposits += 4;
+            assert_eq!(dao.audit_deposits(), (7, 3));
+
+            assert_eq!(dao.reconcile_deposits(), Err(Error::NotCurator));
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.reconcile_deposits(), Ok(()));
+            assert_eq!(dao.audit_deposits(), (3, 3));
+        }
+
+        #[ink::test]
+        fn token_deposit_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { deposit_in_token: true, ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 3, AccountId::from([0x01; 32]), config);
+            let dao_account = ink_env::test::callee::<Env>();
+            set_token_balance(accounts.bob, 10);
+            set_token_balance(dao_account, 0);
+            let native_before = balance_of(dao_account);
+
+            //no value is transferred, the deposit is paid in tokens
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            let proposal_id = dao.new_governance_proposal(ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) }, Vec::new(), 2 * WEEK).unwrap();
+            assert_eq!(dao.get_token_balance(&accounts.bob), 7);
+            assert_eq!(dao.get_token_balance(&dao_account), 3);
+            assert_eq!(dao.proposals[proposal_id as usize].proposal_deposit, 3);
+            assert_eq!((dao.sum_of_token_deposits, dao.sum_of_proposal_deposits), (3, 0));
+            assert_eq!(dao.audit_deposits(), (3, 3));
+            assert_eq!(balance_of(dao_account), native_before);
+
+            //charlie only holds 1 token
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop"), vec![0x02; 5], 2 * WEEK), Err(Error::InsufficientBalance));
+            assert_eq!(dao.number_of_proposals(), 1);
+
+            //the deposit is refunded in tokens
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.vote(proposal_id, true).unwrap();
+            dao.refresh_pre_support(proposal_id);
+            advance_to(dao.proposals[proposal_id as usize].voting_deadline);
+            dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0).unwrap();
+            assert_eq!(dao.get_token_balance(&accounts.bob), 10);
+            assert_eq!(dao.get_token_balance(&dao_account), 0);
+            assert_eq!(dao.sum_of_token_deposits, 0);
+        }
+
+        fn partial_refund_dao() -> Dao {
+            let config = DaoConfig {
+                partial_refund_bps: 5_000,
+                partial_refund_threshold_bps: 5_000,
+                ..Default::default()
+            };
+            //a quorum of 2 tokens for proposals without an amount
+            set_token_total_supply(14);
+            Dao::new_with_config(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]), config)
+        }
+
+        #[ink::test]
+        fn near_miss_gets_partial_refund(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = partial_refund_dao();
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(10);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            //half of the required quorum
+            dao.vote(1, true).unwrap();
+
+            advance_to(dao.proposals[1].voting_deadline);
+            let bob_before = balance_of(accounts.bob);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(balance_of(accounts.bob), bob_before + 5);
+            assert_eq!(dao.proposals[1].open, false);
+            assert_eq!(dao.sum_of_proposal_deposits, 0);
+        }
+
+        #[ink::test]
+        fn deposit_status_follows_the_resolution(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = partial_refund_dao();
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            for description in ["quorum", "near miss", "no show"] {
+                ink_env::test::set_value_transferred::<Env>(10);
+                dao.new_proposal(accounts.alice, 0, Vec::<u8>::from(description), vec![0x02; 5], 2 * WEEK).unwrap();
+            }
+            assert_eq!(dao.deposit_status(1), Some((DepositState::Locked, 10)));
+            assert_eq!(dao.deposit_status(0), None);
+            assert_eq!(dao.deposit_status(4), None);
+
+            //the full quorum for the first proposal, half of it for the second
+            dao.vote(1, true).unwrap();
+            dao.vote(2, true).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, true).unwrap();
+
+            advance_to(dao.proposals[3].voting_deadline);
+            for proposal_id in 1..=3 {
+                assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            }
+            assert_eq!(dao.deposit_status(1), Some((DepositState::Refunded, 10)));
+            assert_eq!(dao.deposit_status(2), Some((DepositState::PartiallyRefunded { refunded: 5 }, 10)));
+            assert_eq!(dao.deposit_status(3), Some((DepositState::Slashed, 10)));
+        }
+
+        #[ink::test]
+        fn no_show_deposit_is_slashed(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = partial_refund_dao();
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(10);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            advance_to(dao.proposals[1].voting_deadline);
+            let bob_before = balance_of(accounts.bob);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(balance_of(accounts.bob), bob_before);
+            assert_eq!(dao.proposals[1].open, false);
+        }
+
+        #[ink::test]
+        fn slashed_deposit_is_redirected_when_configured(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig {
+                partial_refund_bps: 5_000,
+                partial_refund_threshold_bps: 5_000,
+                slash_destination: Some(accounts.eve),
+                ..Default::default()
+            };
+            set_token_total_supply(14);
+            let mut dao = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(10);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            ink_env::test::set_value_transferred::<Env>(10);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            //half of the required quorum for the second proposal
+            dao.vote(2, true).unwrap();
+
+            advance_to(dao.proposals[2].voting_deadline);
+            let treasury = dao.actual_balance();
+            let eve_before = balance_of(accounts.eve);
+
+            //no votes, the whole deposit is slashed
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(balance_of(accounts.eve), eve_before + 10);
+            //near miss, half of the deposit is slashed
+            dao.execute_proposal(2, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(balance_of(accounts.eve), eve_before + 15);
+
+            assert_eq!(dao.actual_balance(), treasury);
+            assert_eq!(dao.sum_of_proposal_deposits, 0);
+        }
+
+        #[ink::test]
+        fn refunded_deposit_is_not_slashed_at_expiry(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { slash_destination: Some(accounts.eve), ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(10);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            //the state a passed proposal is left in when its call returns an error
+            assert!(dao.transfer_deposit(accounts.bob, 10));
+            dao.proposals[1].proposal_passed = true;
+            dao.proposals[1].deposit_state = DepositState::Refunded;
+
+            advance_to(dao.proposals[1].voting_deadline + EXECUTE_PROPOSAL_PERIOD + 1);
+            let bob_before = balance_of(accounts.bob);
+            let eve_before = balance_of(accounts.eve);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(balance_of(accounts.eve), eve_before);
+            assert_eq!(balance_of(accounts.bob), bob_before);
+            assert_eq!(dao.deposit_status(1), Some((DepositState::Refunded, 10)));
+            assert_eq!(dao.proposals[1].open, false);
+            assert_eq!(dao.sum_of_proposal_deposits, 0);
+        }
+
+        #[ink::test]
+        fn slashed_deposit_stays_in_treasury_by_default(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = partial_refund_dao();
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(10);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+
+            advance_to(dao.proposals[1].voting_deadline);
+            let treasury = dao.actual_balance();
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(dao.actual_balance(), treasury + 10);
+        }
+
+        #[ink::test]
+        fn executed_at_is_set_on_execution(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) });
+            assert_eq!(dao.get_proposal(proposal_id).executed_at, None);
+
+            ink_env::test::advance_block::<Env>();
+            let now = ink_env::block_timestamp::<Env>();
+            dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0).unwrap();
+            let p = dao.get_proposal(proposal_id);
+            assert_eq!(p.executed_at, Some(now));
+            assert!(p.executed_at.unwrap() > p.voting_deadline);
+
+            //a failed proposal is never executed
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            let failed_id = dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            advance_to(dao.proposals[failed_id as usize].voting_deadline);
+            dao.execute_proposal(failed_id, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(dao.get_proposal(failed_id).executed_at, None);
+        }
+
+        fn dead_proposal_dao(policy: DeadProposalPolicy) -> Dao {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = DaoConfig { dead_proposal_policy: policy, ..Default::default() };
+            let mut dao = Dao::new_with_config(accounts.alice, 1, AccountId::from([0x01; 32]), config);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao
+        }
+
+        #[ink::test]
+        fn dead_proposal_can_be_killed(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = dead_proposal_dao(DeadProposalPolicy::Refund);
+            //the second proposal has support
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(2, true).unwrap();
+
+            assert_eq!(dao.kill_dead_proposal(1), Err(Error::ProposalNotDead));
+
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+            let bob_before = balance_of(accounts.bob);
+            assert_eq!(dao.kill_dead_proposal(1), Ok(()));
+            assert_eq!(dao.proposals[1].open, false);
+            assert_eq!(balance_of(accounts.bob), bob_before + 2);
+            assert_eq!(dao.deposit_status(1), Some((DepositState::Refunded, 2)));
+            assert_eq!(dao.kill_dead_proposal(1), Err(Error::ProposalNotDead));
+
+            //the killed proposal takes no more votes
+            assert_eq!(dao.vote(1, true), Err(Error::OutsideDeadline));
+            assert_eq!(dao.proposals[1].yea, 0);
+            assert_eq!(dao.vote_of(1, accounts.charlie), None);
+
+            //an engaged proposal can not be killed
+            assert_eq!(dao.kill_dead_proposal(2), Err(Error::ProposalNotDead));
+            assert_eq!(dao.proposals[2].open, true);
+            assert_eq!(dao.sum_of_proposal_deposits, 2);
+        }
+
+        #[ink::test]
+        fn votes_on_a_killed_proposal_are_kept(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = dead_proposal_dao(DeadProposalPolicy::Refund);
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+            assert_eq!(dao.kill_dead_proposal(1), Ok(()));
+
+            //the tally of the closed proposal does not change anymore
+            assert_eq!(dao.unvotable_proposals(accounts.charlie), Vec::<u64>::new());
+            assert_eq!(dao.un_vote(1), Err(Error::OutsideDeadline));
+            dao.un_vote_all();
+            set_token_balance(accounts.charlie, 0);
+            assert_eq!(dao.prune_vote(1, accounts.charlie), Err(Error::OutsideDeadline));
+            assert_eq!(dao.proposals[1].nay, 1);
+            assert_eq!(dao.vote_of(1, accounts.charlie), Some(VotePosition::No));
+        }
+
+        #[ink::test]
+        fn frozen_dead_proposal_can_not_be_killed(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = dead_proposal_dao(DeadProposalPolicy::Slash);
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            dao.freeze_proposal(1).unwrap();
+
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+            assert_eq!(dao.kill_dead_proposal(1), Err(Error::ProposalFrozen));
+            assert_eq!(dao.proposals[1].open, true);
+            assert_eq!(dao.deposit_status(1), Some((DepositState::Locked, 2)));
+
+            dao.unfreeze_proposal(1).unwrap();
+            assert_eq!(dao.kill_dead_proposal(1), Ok(()));
+        }
+
+        #[ink::test]
+        fn dead_proposal_deposit_slashed_when_configured(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = dead_proposal_dao(DeadProposalPolicy::Slash);
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+
+            let bob_before = balance_of(accounts.bob);
+            let treasury = dao.actual_balance();
+            assert_eq!(dao.kill_dead_proposal(1), Ok(()));
+            assert_eq!(balance_of(accounts.bob), bob_before);
+            assert_eq!(dao.actual_balance(), treasury + 2);
+            assert_eq!(dao.deposit_status(1), Some((DepositState::Slashed, 2)));
+            assert_eq!(dao.deposit_status(2), Some((DepositState::Locked, 2)));
+
+            //killing is disabled by default
+            let mut dao = dead_proposal_dao(DeadProposalPolicy::Disabled);
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+            assert_eq!(dao.kill_dead_proposal(1), Err(Error::ProposalNotDead));
+        }
+
+        #[ink::test]
+        fn passed_proposal_gets_full_refund(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = partial_refund_dao();
+            set_token_balance(accounts.bob, 2);
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) });
+            let bob_before = balance_of(accounts.bob);
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Ok(()));
+            assert_eq!(balance_of(accounts.bob), bob_before + 2);
+        }
+
+        #[ink::test]
+        fn to_display_units_works(){
+            let config = DaoConfig { decimals: 10, ..Default::default() };
+            let dao = Dao::new_with_config(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]), config);
+            assert_eq!(dao.decimals(), 10);
+
+            assert_eq!(dao.to_display_units(9_999_999_999), (0, 9_999_999_999));
+            assert_eq!(dao.to_display_units(10_000_000_000), (1, 0));
+            assert_eq!(dao.to_display_units(25_000_000_001), (2, 5_000_000_001));
+        }
+
+        #[ink::test]
+        fn quorum_met_history_works(){
+            let mut dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.quorum_met_history(), Vec::<Timestamp>::new());
+
+            let first = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) });
+            let first_time = ink_env::block_timestamp::<Env>();
+            dao.execute_proposal(first, vec![0; 4], Vec::new(), 0).unwrap();
+
+            let second = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x01; 32]) });
+            let second_time = ink_env::block_timestamp::<Env>();
+            dao.execute_proposal(second, vec![0; 4], Vec::new(), 0).unwrap();
+
+            assert!(first_time < second_time);
+            assert_eq!(dao.quorum_met_history(), vec![first_time, second_time]);
+            assert_eq!(dao.last_time_min_quorum_met, second_time);
+        }
+
+        #[ink::test]
+        fn quorum_met_history_is_capped(){
+            let mut dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            for now in 0..MAX_QUORUM_MET_HISTORY as u64 + 2 {
+                dao.record_quorum_met(now);
+            }
+
+            let history = dao.quorum_met_history();
+            assert_eq!(history.len(), MAX_QUORUM_MET_HISTORY);
+            //the oldest entries were dropped
+            assert_eq!(history[0], 2);
+            assert_eq!(*history.last().unwrap(), MAX_QUORUM_MET_HISTORY as u64 + 1);
+        }
+
+        #[ink::test]
+        fn executed_proposals_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+
+            //a proposal nobody votes for fails
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            advance_to(dao.proposals[1].voting_deadline);
+            dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0).unwrap();
+
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) });
+            dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0).unwrap();
+
+            assert_eq!(dao.proposal_passed(1), Some(false));
+            assert_eq!(dao.proposal_passed(proposal_id), Some(true));
+            assert_eq!(dao.proposal_passed(0), None);
+            assert_eq!(dao.proposal_passed(3), None);
+            assert_eq!(dao.executed_proposals(), vec![proposal_id]);
+        }
+
+        #[ink::test]
+        fn proposal_with_a_failed_call_is_not_executed(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            stub_calls_to(accounts.alice, false);
+
+            //the proposal passes, but its call fails
+            assert_eq!(dao.execute_proposal(1, vec![1, 2, 3, 4], vec![0x02; 5], 0), Err(Error::TransactionFailed));
+            assert_eq!(stubbed_calls(), vec![(accounts.alice, [1, 2, 3, 4], 0)]);
+            assert_eq!(dao.proposal_passed(1), Some(true));
+            assert_eq!(dao.get_proposal(1).executed_at, None);
+            assert_eq!(dao.executed_proposals(), Vec::<u64>::new());
+
+            //closed once the execution period is over, without counting as passed
+            advance_to(dao.proposals[1].voting_deadline + EXECUTE_PROPOSAL_PERIOD + 1);
+            assert_eq!(dao.execute_proposal(1, vec![1, 2, 3, 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(dao.proposals[1].open, false);
+            assert_eq!(dao.executed_proposals(), Vec::<u64>::new());
+            assert_eq!(dao.stats().total_passed, 0);
+            assert_eq!(dao.stats().total_failed, 1);
+        }
+
+        #[ink::test]
+        fn dry_run_of_a_succeeding_call_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            stub_calls_to(accounts.alice, true);
+            assert_eq!(dao.execute_for_dry_run(1, vec![1, 2, 3, 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(stubbed_calls(), vec![(accounts.alice, [1, 2, 3, 4], 0)]);
+        }
+
+        #[ink::test]
+        fn dry_run_of_a_reverting_call_fails(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            stub_calls_to(accounts.alice, false);
+            assert_eq!(dao.execute_for_dry_run(1, vec![1, 2, 3, 4], vec![0x02; 5], 0), Err(Error::TransactionFailed));
+            assert_eq!(stubbed_calls(), vec![(accounts.alice, [1, 2, 3, 4], 0)]);
+        }
+
+        #[ink::test]
+        fn dry_run_fails_before_the_call(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 1, AccountId::from([0x01; 32]));
+            stub_calls_to(accounts.alice, true);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            ink_env::test::set_value_transferred::<Env>(2);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+            dao.refresh_pre_support(1);
+
+            //the debate is still running
+            assert_eq!(dao.execute_for_dry_run(1, vec![1, 2, 3, 4], vec![0x02; 5], 0), Err(Error::ProposalExecutionFailed));
+            advance_to(dao.proposals[1].voting_deadline);
+            //the data does not match the proposal
+            assert_eq!(dao.execute_for_dry_run(1, vec![1, 2, 3, 4], vec![0x03; 5], 0), Err(Error::ProposalExecutionFailed));
+            //closed without passing, nobody voted for it
+            assert_eq!(dao.execute_for_dry_run(2, vec![1, 2, 3, 4], vec![0x02; 5], 0), Err(Error::ProposalExecutionFailed));
+            assert_eq!(stubbed_calls(), Vec::new());
+        }
+
+        #[ink::test]
+        fn proposal_with_a_successful_call_is_executed(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = high_quorum_setup(vec![0x02; 5]);
+            stub_calls_to(accounts.alice, true);
+
+            assert_eq!(dao.execute_proposal(1, vec![1, 2, 3, 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(stubbed_calls(), vec![(accounts.alice, [1, 2, 3, 4], 0)]);
+            assert!(dao.get_proposal(1).executed_at.is_some());
+            assert_eq!(dao.executed_proposals(), vec![1]);
+            assert_eq!(dao.stats().total_passed, 1);
+        }
+
+        #[ink::test]
+        fn contract_address_works(){
+            let dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            assert_eq!(dao.contract_address(), ink_env::test::callee::<Env>());
+            //the DAO can always pay itself
+            assert_eq!(dao.allowed_recipients.get(dao.contract_address()), Some(true));
+        }
+
+        #[ink::test]
+        fn encode_call_input_works(){
+            let dao = Dao::new(AccountId::from([0x01; 32]), 1, AccountId::from([0x01; 32]));
+            //the arguments of a call taking (u32, bool)
+            let transaction_data = scale::Encode::encode(&(7u32, true));
+            assert_eq!(dao.encode_call_input(transaction_data.clone()), vec![7, 0, 0, 0, 1]);
+            //unlike a `Vec<u8>` argument, there is no length prefix
+            assert_ne!(dao.encode_call_input(transaction_data.clone()), scale::Encode::encode(&transaction_data));
+            assert_eq!(dao.encode_call_input(Vec::new()), Vec::<u8>::new());
+
+            //the same bytes follow the selector in the input of the call
+            let input = ExecutionInput::new(Selector::from([1, 2, 3, 4])).push_arg(CallInput(&transaction_data));
+            let mut expected = vec![1, 2, 3, 4];
+            expected.extend(dao.encode_call_input(transaction_data.clone()));
+            assert_eq!(scale::Encode::encode(&input), expected);
+        }
+
+        #[ink::test]
+        fn stats_works(){
+            let mut dao = dead_proposal_dao(DeadProposalPolicy::Refund);
+            assert_eq!(dao.stats(), DaoStats { total_created: 2, ..Default::default() });
+
+            //cancelled
+            advance_to(dao.proposals[1].voting_deadline - PRE_SUPPORT_TIME);
+            dao.kill_dead_proposal(1).unwrap();
+
+            //failed
+            advance_to(dao.proposals[2].voting_deadline);
+            dao.execute_proposal(2, vec![0; 4], vec![0x02; 5], 0).unwrap();
+
+            //passed
+            let proposal_id = pass_governance_proposal(&mut dao, ProposalKind::ChangeToken { new_token: AccountId::from([0x09; 32]) });
+            assert_eq!(dao.stats().total_created, 3);
+            dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0).unwrap();
+            //executing a closed proposal again does not count it twice
+            assert_eq!(dao.execute_proposal(proposal_id, vec![0; 4], Vec::new(), 0), Err(Error::ProposalExecutionFailed));
+
+            assert_eq!(dao.stats(), DaoStats {
+                total_created: 3,
+                total_passed: 1,
+                total_failed: 1,
+                total_cancelled: 1,
+            });
+        }
+
+        #[ink::test]
+        fn freeze_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 0, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.vote(1, true).unwrap();
+
+            assert_eq!(dao.freeze_proposal(1), Err(Error::NotCurator));
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.freeze_proposal(1), Ok(()));
+            assert_eq!(dao.freeze_proposal(1), Err(Error::NoChange));
+
+            //only the frozen proposal is affected
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(dao.vote(1, true), Err(Error::ProposalFrozen));
+            assert_eq!(dao.vote(2, true), Ok(()));
+            assert_eq!(dao.proposals[1].yea, 1);
+
+            //the votes on the frozen proposal can not be withdrawn either
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.un_vote(1), Err(Error::ProposalFrozen));
+            assert_eq!(dao.unvotable_proposals(accounts.bob), Vec::<u64>::new());
+            dao.un_vote_all();
+            assert_eq!(dao.proposals[1].yea, 1);
+            assert_eq!(dao.vote_of(1, accounts.bob), Some(VotePosition::Yes));
+
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Err(Error::ProposalFrozen));
+            assert_eq!(dao.proposals[1].open, true);
+            assert_eq!(dao.execute_proposal(2, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(dao.unfreeze_proposal(1), Err(Error::NotCurator));
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(dao.unfreeze_proposal(1), Ok(()));
+            assert_eq!(dao.unfreeze_proposal(1), Err(Error::NoChange));
+            assert_eq!(dao.execute_proposal(1, vec![0; 4], vec![0x02; 5], 0), Ok(()));
+            assert_eq!(dao.proposals[1].open, false);
+        }
+
+        #[ink::test]
+        fn total_active_engagement_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 0, AccountId::from([0x01; 32]));
+            set_token_balance(accounts.bob, 5);
+            set_token_balance(accounts.charlie, 3);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.total_active_engagement(), 0);
+
+            dao.vote(1, true).unwrap();
+            dao.vote(2, false).unwrap();
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+            //bob's 5 tokens count for both proposals
+            assert_eq!(dao.total_active_engagement(), 5 + 3 + 5);
+
+            //closed proposals are not counted
+            dao.close_proposal(2);
+            assert_eq!(dao.total_active_engagement(), 5 + 3);
+        }
+
+        #[ink::test]
+        fn unvotable_proposals_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 0, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 3 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 3"), vec![0x02; 5], 4 * WEEK).unwrap();
+            assert_eq!(dao.unvotable_proposals(accounts.bob), Vec::<u64>::new());
+
+            dao.vote(1, true).unwrap();
+            dao.vote(2, true).unwrap();
+            //a changed vote is listed once
+            dao.vote(2, false).unwrap();
+            dao.vote(3, true).unwrap();
+            assert_eq!(dao.unvotable_proposals(accounts.bob), vec![1, 2, 3]);
+            assert_eq!(dao.unvotable_proposals(accounts.charlie), Vec::<u64>::new());
+
+            //past its deadline
+            advance_to(dao.proposals[1].voting_deadline);
+            assert_eq!(dao.unvotable_proposals(accounts.bob), vec![2, 3]);
+
+            //closed early
+            dao.close_proposal(3);
+            assert_eq!(dao.unvotable_proposals(accounts.bob), vec![2]);
+        }
+
+        #[ink::test]
+        fn blocking_proposal_works(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 0, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 3 * WEEK).unwrap();
+            assert_eq!(dao.blocking_proposal(accounts.bob), None);
+
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.blocking_proposal(accounts.bob), Some((1, dao.proposals[1].voting_deadline)));
+            //the proposal with the later deadline blocks
+            dao.vote(2, true).unwrap();
+            let deadline = dao.proposals[2].voting_deadline;
+            assert_eq!(dao.blocking_proposal(accounts.bob), Some((2, deadline)));
+
+            //closed without support
+            advance_to(deadline);
+            dao.execute_proposal(2, vec![0; 4], vec![0x02; 5], 0).unwrap();
+            assert_eq!(dao.proposals[2].open, false);
+            assert_eq!(dao.blocking_proposal(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn vote_seq_counts_votes(){
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut dao = Dao::new(accounts.alice, 0, AccountId::from([0x01; 32]));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 1"), vec![0x02; 5], 2 * WEEK).unwrap();
+            dao.new_proposal(accounts.alice, 0, Vec::<u8>::from("prop 2"), vec![0x02; 5], 2 * WEEK).unwrap();
+            assert_eq!(dao.vote_seq(), 0);
+
+            dao.vote(1, true).unwrap();
+            assert_eq!(dao.vote_seq(), 1);
+            dao.vote(2, false).unwrap();
+            assert_eq!(dao.vote_seq(), 2);
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.vote_seq(), 3);
+
+            //changing a vote is a new vote
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            dao.vote(1, false).unwrap();
+            assert_eq!(dao.vote_seq(), 4);
+
+            //rejected votes and un-votes are not counted
+            assert_eq!(dao.vote(1, false), Err(Error::NoChange));
+            dao.un_vote(1).unwrap();
+            assert_eq!(dao.vote_seq(), 4);
+        }
+
         #[ink::test]
         fn unblock_me_works(){
             let accounts =
@@ -899,7 +4169,7 @@ mod dao {
 
             //should be false before a vote takes place
             assert_eq!(dao.unblock_me(), false);
-            dao.vote(1, true);
+            dao.vote(1, true).unwrap();
             assert_eq!(dao.unblock_me(), true);
 
         }
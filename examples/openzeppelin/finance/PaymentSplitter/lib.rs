@@ -174,5 +174,52 @@ mod payment_splitter {
 
     #[cfg(test)]
     mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        type Env = ink_env::DefaultEnvironment;
+
+        fn add_to_contract_balance(amount: Balance) {
+            let contract = ink_env::test::callee::<Env>();
+            let balance = ink_env::test::get_account_balance::<Env>(contract).unwrap();
+            ink_env::test::set_account_balance::<Env>(contract, balance + amount);
+        }
+
+        #[ink::test]
+        fn released_totals_follow_shares() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 0);
+            let mut splitter = PaymentSplitter::new(vec![accounts.bob, accounts.charlie], vec![1, 3]);
+
+            add_to_contract_balance(1000);
+            splitter.release(accounts.bob);
+            assert_eq!(splitter.released(accounts.bob), 250);
+            assert_eq!(splitter.released(accounts.charlie), 0);
+            assert_eq!(splitter.total_released(), 250);
+
+            //a later payment is split the same way
+            ink_env::test::advance_block::<Env>();
+            add_to_contract_balance(400);
+            splitter.release(accounts.charlie);
+            splitter.release(accounts.bob);
+            assert_eq!(splitter.released(accounts.bob), 350);
+            assert_eq!(splitter.released(accounts.charlie), 1050);
+            assert_eq!(
+                splitter.total_released(),
+                splitter.released(accounts.bob) + splitter.released(accounts.charlie)
+            );
+            assert_eq!(splitter.total_released(), 1400);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "PaymentSplitter: account is not due payment")]
+        fn release_without_new_payments_fails() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), 100);
+            let mut splitter = PaymentSplitter::new(vec![accounts.bob], vec![1]);
+            splitter.release(accounts.bob);
+            splitter.release(accounts.bob);
+        }
     }
 }
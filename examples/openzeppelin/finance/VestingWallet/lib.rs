@@ -15,9 +15,29 @@ use ink_lang as ink;
 mod vesting_wallet {
 
     use ink_storage::{
-        traits::SpreadAllocate,
+        traits::{
+            PackedLayout,
+            SpreadAllocate,
+            SpreadLayout,
+        },
+    };
+    use ink_prelude::vec::Vec;
+    use ink_primitives::{
+        Key,
+        KeyPtr,
+    };
+    use ink_env::call::{
+        build_call,
+        Call,
+        ExecutionInput,
+        Selector,
     };
 
+    /// The maximum number of points returned by `schedule_preview`
+    const MAX_PREVIEW_POINTS: u32 = 100;
+    /// The largest `decimals` for which `10^decimals` fits in a `Balance`
+    const MAX_DECIMALS: u8 = 38;
+
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct VestingWallet {
@@ -25,25 +45,555 @@ mod vesting_wallet {
         beneficiary: AccountId,
         start: Timestamp,
         duration: u64,
+        /// Optional behaviour chosen at instantiation
+        config: VestingConfig,
+        /// True once the beneficiary has acknowledged the grant terms
+        acknowledged: bool,
+        /// Value sent to the wallet through its payable entry points
+        total_funded: Balance,
+        /// Independent vesting schedules of a multi-tranche grant
+        tranches: Vec<Tranche>,
+        /// Number of releases so far
+        release_nonce: u64,
+        /// Destinations and their share (in basis points) of every release.
+        /// Empty sends everything to the beneficiary.
+        split_destinations: Vec<(AccountId, u16)>,
+        /// Time of the latest release
+        last_release: Timestamp,
+        /// The issuer of the grant, the account that instantiated the wallet
+        owner: AccountId,
+        /// True while the beneficiary waits for the owner to accelerate the vesting
+        acceleration_requested: bool,
+        /// Time the owner paused releases, `None` while not paused
+        paused_at: Option<Timestamp>,
+        /// `accrued_unclaimed` as of the latest `release` or `fund`, to detect crossed milestones
+        last_accrued: Balance,
+        /// The holder of the right to the released funds, initially the beneficiary.
+        /// `beneficiary` keeps the original grantee for the record.
+        claim_owner: AccountId,
+    }
+
+    /// A part of a grant with its own linear schedule
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        SpreadAllocate,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Tranche {
+        pub start: Timestamp,
+        pub duration: u64,
+        pub allocation: Balance,
+    }
+
+    impl ink_storage::traits::PackedAllocate for Tranche {
+        fn allocate_packed(&mut self, _at: &Key) {}
+    }
+
+    /// Everything a keeper needs to decide whether calling `release` is worthwhile,
+    /// as returned by `keeper_status`
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct KeeperStatus {
+        /// Amount `release` would pay out if it were allowed now
+        pub releasable: Balance,
+        /// Releases are paused, see `paused`
+        pub is_paused: bool,
+        /// End of the `min_release_interval` after the latest release, `None` if not throttled
+        pub throttled_until: Option<Timestamp>,
+        /// The next time more funds vest: the start of a vesting that has not started yet,
+        /// the current time while funds vest, `None` once everything has vested
+        pub next_unlock: Option<Timestamp>,
+    }
+
+    /// The state of the grant at the current block, as returned by `claim_summary`.
+    /// `released + releasable + unvested == allocation`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ClaimSummary {
+        /// Everything the wallet has held, see `total_allocation`
+        pub allocation: Balance,
+        pub released: Balance,
+        pub releasable: Balance,
+        /// Part of the allocation that has not vested yet
+        pub unvested: Balance,
+        /// Share of the allocation vested so far, in basis points
+        pub vested_bps: u16,
+        /// Time everything will have vested, see `seconds_until_fully_vested`
+        pub fully_vested_at: Timestamp,
+    }
+
+    /// The schedule of a wallet as returned by `export`, used to check that a
+    /// replacement wallet vests the same way (see `schedules_equivalent`)
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct VestingExport {
+        pub start: Timestamp,
+        pub duration: u64,
+        pub tranches: Vec<Tranche>,
+        pub rounding: RoundingMode,
+        pub tail_bonus_bps: u16,
+        pub tail_window: u64,
+        pub released: Balance,
+    }
+
+    /// Optional settings of a wallet, supplied through `new_with_config`.
+    /// `new` uses the default (every option turned off).
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        SpreadAllocate,
+        Default,
+        Clone,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct VestingConfig {
+        /// The beneficiary has to `acknowledge` the grant before anything can be released
+        pub require_ack: bool,
+        /// Rounding of the vested amount while the vesting is in progress
+        pub rounding: RoundingMode,
+        /// How released funds are sent to the beneficiary
+        pub delivery: DeliveryMode,
+        /// Only the beneficiary may call `release`
+        pub restrict_release: bool,
+        /// Decimals of the vested currency, used by `to_display_units`
+        pub decimals: u8,
+        /// Upper limit of `total_funded`, `None` for no limit
+        pub max_allocation: Option<Balance>,
+        /// The terms of the grant can not be changed after instantiation.
+        /// Messages changing them return `Error::Immutable`.
+        pub immutable: bool,
+        /// Allow a `start` before the instantiation. Part of the grant is vested right away,
+        /// which is recorded in a `BackdatedVesting` event.
+        pub allow_past_start: bool,
+        /// Minimum number of seconds between two releases, 0 for no limit
+        pub min_release_interval: u64,
+        /// Receives a released amount with a plain transfer if delivering it to its
+        /// destination fails, e.g. a contract beneficiary rejecting transfers.
+        /// `None` makes a failed delivery fail the release.
+        pub fallback: Option<AccountId>,
+        /// Seconds after which a pause ends on its own, so an unresponsive owner can not
+        /// lock the beneficiary out. `None` keeps the wallet paused until `unpause`.
+        pub auto_unpause_after: Option<u64>,
+        /// Share of the allocation (in basis points) held back as a retention bonus.
+        /// The rest vests linearly, the bonus unlocks in one go once the final
+        /// `tail_window` seconds of the vesting have begun.
+        pub tail_bonus_bps: u16,
+        /// Length of the window before the end of the vesting in which the bonus is unlocked
+        pub tail_window: u64,
+        /// Amounts of `accrued_unclaimed` for which `release` and `fund` emit an
+        /// `AccrualThresholdCrossed` event when they are reached
+        pub accrual_milestones: Vec<Balance>,
+        /// Contract asked before every release whether the beneficiary may still receive
+        /// funds, e.g. for grants that have to halt when a compliance check fails.
+        /// `None` releases without asking.
+        pub compliance_oracle: Option<AccountId>,
+        /// Selector of the oracle's `is_allowed(beneficiary: AccountId) -> bool` message
+        pub compliance_selector: [u8; 4],
+    }
+
+    /// Direction in which the linear vesting formula rounds.
+    /// The full allocation is vested at the end of the schedule in either mode.
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum RoundingMode {
+        /// Round down, in favor of the contract (OpenZeppelin's behaviour)
+        Down,
+        /// Round up, in favor of the beneficiary
+        Up,
+    }
+
+    impl Default for RoundingMode {
+        fn default() -> Self {
+            RoundingMode::Down
+        }
+    }
+
+    //`SpreadAllocate` can not be derived for enums
+    impl SpreadAllocate for RoundingMode {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.advance_by(1);
+            Self::default()
+        }
+    }
+
+    /// How `release` delivers funds to the beneficiary
+    #[derive(
+        Debug,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum DeliveryMode {
+        /// A plain balance transfer
+        Transfer,
+        /// Call the message with `selector` on a contract beneficiary, sending the
+        /// released amount as the transferred value. The message has to be payable
+        /// and take no arguments.
+        Call { selector: [u8; 4] },
+    }
+
+    impl Default for DeliveryMode {
+        fn default() -> Self {
+            DeliveryMode::Transfer
+        }
+    }
+
+    //`SpreadAllocate` can not be derived for enums
+    impl SpreadAllocate for DeliveryMode {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.advance_by(1);
+            Self::default()
+        }
     }
 
     /// event for when a new payee is added
     #[ink(event)]
     pub struct TokensReleased{
+        amount: Balance,
+        /// Order of magnitude of `amount`, see `amount_bucket`.
+        /// Lets listeners subscribe to releases of a certain size.
         #[ink(topic)]
+        bucket: u8,
+        /// Increases by one with every release, so listeners can detect missed events
+        nonce: u64,
+    }
+
+    /// event for when a released amount is sent to the fallback account
+    /// because it could not be delivered to its destination
+    #[ink(event)]
+    pub struct FallbackDelivery{
+        #[ink(topic)]
+        intended: AccountId,
+        fallback: AccountId,
         amount: Balance,
     }
 
+    /// event for when the owner pauses releases
+    #[ink(event)]
+    pub struct Paused{
+        at: Timestamp,
+    }
+
+    /// event for when the owner lifts a pause
+    #[ink(event)]
+    pub struct Unpaused{
+        at: Timestamp,
+    }
+
+    /// event for when the right to the released funds changes hands
+    #[ink(event)]
+    pub struct ClaimTransferred{
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    /// event for when the vested but unreleased amount reaches one of the `accrual_milestones`
+    #[ink(event)]
+    pub struct AccrualThresholdCrossed{
+        #[ink(topic)]
+        milestone: Balance,
+        accrued: Balance,
+    }
+
+    /// event for when the owner replaces the code of the wallet
+    #[ink(event)]
+    pub struct CodeUpgraded{
+        code_hash: Hash,
+    }
+
+    /// event for when a wallet is instantiated with a start in the past
+    #[ink(event)]
+    pub struct BackdatedVesting{
+        start: Timestamp,
+        /// Amount vested at instantiation
+        vested: Balance,
+    }
+
+    /// event for when the beneficiary asks for the vesting to be accelerated
+    #[ink(event)]
+    pub struct AccelerationRequested{
+        #[ink(topic)]
+        beneficiary: AccountId,
+    }
+
+    /// event for when the owner accelerates the vesting, vesting everything
+    #[ink(event)]
+    pub struct AccelerationApproved{
+        start: Timestamp,
+        duration: u64,
+    }
+
+    /// event for when the owner replaces the schedule, keeping what was released
+    #[ink(event)]
+    pub struct Rebased {
+        start: Timestamp,
+        duration: u64,
+        released: Balance,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// The caller is not the beneficiary of the wallet
+        NotBeneficiary,
+        /// The beneficiary has not acknowledged the grant terms yet
+        NotAcknowledged,
+        /// Nothing has vested since the last release
+        NothingToRelease,
+        /// The shares of the split destinations do not add up to 10000 basis points
+        InvalidSplit,
+        /// The wallet was instantiated as immutable
+        Immutable,
+        /// `min_release_interval` has not passed since the last release
+        TooSoon,
+        /// The caller is not the owner of the wallet
+        NotOwner,
+        /// The beneficiary has not requested an acceleration
+        NoAccelerationRequested,
+        /// Releases are paused by the owner
+        Paused,
+        /// The caller does not hold the claim to the released funds
+        NotClaimOwner,
+        /// The claim can not be transferred to the zero account or the wallet itself
+        InvalidClaimOwner,
+        /// The code could not be replaced, e.g. no code with the hash was uploaded
+        UpgradeFailed,
+        /// The compliance oracle did not allow a release to the beneficiary
+        ComplianceBlocked,
+        /// The released amount could neither be delivered nor sent to the fallback account
+        TransferFailed,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    // Counts how often the wallet balance is read, since the off-chain
+    // environment has no way to count calls into it.
+    #[cfg(test)]
+    thread_local! {
+        static BALANCE_READS: core::cell::Cell<u32> = core::cell::Cell::new(0);
+    }
+
+    // Accounts transfers to which fail, since the off-chain environment
+    // has no way to make a transfer fail.
+    #[cfg(test)]
+    thread_local! {
+        static REJECTING_ACCOUNTS: core::cell::RefCell<Vec<AccountId>> = core::cell::RefCell::new(Vec::new());
+    }
+
+    // The calls made by `DeliveryMode::Call` as `(callee, selector, transferred value)`,
+    // since the off-chain environment can not invoke contracts.
+    #[cfg(test)]
+    thread_local! {
+        static DELIVERY_CALLS: core::cell::RefCell<Vec<(AccountId, [u8; 4], Balance)>> = core::cell::RefCell::new(Vec::new());
+    }
+
+    // The answers of compliance oracles by oracle account, and the questions asked as
+    // `(oracle, selector, beneficiary)`, since the off-chain environment can not invoke contracts.
+    #[cfg(test)]
+    thread_local! {
+        static ORACLE_ANSWERS: core::cell::RefCell<Vec<(AccountId, bool)>> = core::cell::RefCell::new(Vec::new());
+        static ORACLE_QUERIES: core::cell::RefCell<Vec<(AccountId, [u8; 4], AccountId)>> = core::cell::RefCell::new(Vec::new());
+    }
+
+    /// The `bucket` topic of `TokensReleased`: the number of decimal digits of
+    /// `amount` minus one, i.e. `floor(log10(amount))`. 1-9 is bucket 0, 10-99
+    /// bucket 1, 100-999 bucket 2 and so on. An amount of 0 is bucket 0.
+    fn amount_bucket(amount: Balance) -> u8 {
+        let mut bucket = 0;
+        let mut amount = amount / 10;
+        while amount > 0 {
+            bucket += 1;
+            amount /= 10;
+        }
+        bucket
+    }
+
+    /// `a * b / c` and its remainder, without overflowing as long as the result fits,
+    /// which it does for `b <= c`. Vesting schedules multiply by less than they divide by.
+    ///
+    /// `a * b` is built bit by bit of `b` as `q * c + r` with `r < c`, so no intermediate
+    /// product is formed. The loop runs once per bit of `b`.
+    fn mul_div(a: u128, b: u128, c: u128) -> (u128, u128) {
+        let (qa, ra) = (a / c, a % c);
+        let (mut q, mut r) = (0, 0);
+        for bit in (0..128 - b.leading_zeros()).rev() {
+            // double, `r + r >= c` without overflowing
+            q *= 2;
+            if r >= c - r {
+                q += 1;
+                r -= c - r;
+            } else {
+                r += r;
+            }
+            // add `a` for a set bit
+            if (b >> bit) & 1 == 1 {
+                q += qa;
+                if r >= c - ra {
+                    q += 1;
+                    r -= c - ra;
+                } else {
+                    r += ra;
+                }
+            }
+        }
+        (q, r)
+    }
+
     impl VestingWallet {
         /// Constructor that initializes the `bool` value to the given `init_value`.
         #[ink(constructor)]
         pub fn new(beneficiary: AccountId, start: Timestamp, duration_seconds: u64) -> Self {
-            Self {
+            Self::new_with_config(beneficiary, start, duration_seconds, VestingConfig::default())
+        }
+
+        /// Same as `new`, but with the optional behaviour described by `config`
+        ///
+        /// Panics if `beneficiary` is the zero account, as vested funds sent there can not be spent.
+        /// Panics if `beneficiary` is the wallet itself, releases would only count as released.
+        /// Panics if `start` is in the past, unless `allow_past_start` is set.
+        #[ink(constructor)]
+        pub fn new_with_config(beneficiary: AccountId, start: Timestamp, duration_seconds: u64, config: VestingConfig) -> Self {
+            assert!(beneficiary != AccountId::from([0u8; 32]), "beneficiary is the zero account");
+            assert!(beneficiary != Self::env().account_id(), "beneficiary is the wallet itself");
+            assert!(config.decimals <= MAX_DECIMALS, "decimals above 38");
+            assert!(config.tail_bonus_bps <= 10_000, "tail bonus above 100%");
+            let now = Self::env().block_timestamp();
+            assert!(start >= now || config.allow_past_start, "start is in the past");
+
+            let wallet = Self {
                 released: 0,
                 beneficiary: beneficiary,
                 start: start,
                 duration: duration_seconds,
+                config: config,
+                acknowledged: false,
+                total_funded: 0,
+                tranches: Vec::new(),
+                release_nonce: 0,
+                split_destinations: Vec::new(),
+                last_release: 0,
+                owner: Self::env().caller(),
+                acceleration_requested: false,
+                paused_at: None,
+                claim_owner: beneficiary,
+                last_accrued: 0,
+            };
+
+            if start < now {
+                Self::env().emit_event(BackdatedVesting {
+                    start,
+                    vested: wallet.vested_amount(now),
+                });
+            }
+            wallet
+        }
+
+        /// Same as `new`, but with the end of the vesting instead of its duration.
+        ///
+        /// Panics if `end` is not after `start`.
+        #[ink(constructor)]
+        pub fn new_with_end(beneficiary: AccountId, start: Timestamp, end: Timestamp) -> Self {
+            let duration_seconds = end.checked_sub(start).filter(|duration| *duration > 0)
+                .expect("end is not after start");
+            Self::new(beneficiary, start, duration_seconds)
+        }
+
+        /// Same as `new`, but with independent schedules for each of `tranches`, which may overlap.
+        /// The overall vesting runs from the earliest tranche start to the latest tranche end.
+        ///
+        /// Panics if `tranches` is empty, a tranche has a zero duration, or the end of a tranche
+        /// or the sum of the allocations overflows.
+        #[ink(constructor)]
+        pub fn new_tranched(beneficiary: AccountId, tranches: Vec<Tranche>) -> Self {
+            assert!(!tranches.is_empty(), "no tranches");
+            assert!(tranches.iter().all(|t| t.duration > 0), "tranche with zero duration");
+            tranches.iter().try_fold(0 as Balance, |total, t| total.checked_add(t.allocation))
+                .expect("tranche allocations overflow");
+
+            let start = tranches.iter().map(|t| t.start).min().unwrap();
+            let end = tranches.iter()
+                .map(|t| t.start.checked_add(t.duration).expect("tranche end overflows"))
+                .max().unwrap();
+            let mut wallet = Self::new(beneficiary, start, end - start);
+            wallet.tranches = tranches;
+            wallet
+        }
+
+        /// Same as `new`, but the grant is funded with the value sent along with the instantiation
+        #[ink(constructor, payable)]
+        pub fn new_funded(beneficiary: AccountId, start: Timestamp, duration_seconds: u64) -> Self {
+            let mut wallet = Self::new(beneficiary, start, duration_seconds);
+            wallet.total_funded = Self::env().transferred_value();
+            wallet
+        }
+
+        /// Adds the transferred value to the grant.
+        ///
+        /// Panics (reverting the transfer) if `total_funded` would exceed `max_allocation`.
+        #[ink(message, payable)]
+        pub fn fund(&mut self) {
+            let total_funded = self.total_funded + self.env().transferred_value();
+            if let Some(max_allocation) = self.config.max_allocation {
+                assert!(total_funded <= max_allocation, "funding exceeds the maximum allocation");
             }
+
+            self.total_funded = total_funded;
+            self.record_accrued(self.accrued_unclaimed());
+        }
+
+        /// Returns true if the terms of the grant can never be changed
+        #[ink(message)]
+        pub fn is_immutable(&self) -> bool {
+            self.config.immutable
+        }
+
+        /// Upper limit of the value the wallet can be funded with, `None` if unlimited
+        #[ink(message)]
+        pub fn max_allocation(&self) -> Option<Balance> {
+            self.config.max_allocation
         }
 
         #[ink(message)]
@@ -51,6 +601,185 @@ mod vesting_wallet {
             self.beneficiary
         }
 
+        /// The account released funds are paid to
+        #[ink(message)]
+        pub fn claim_owner(&self) -> AccountId {
+            self.claim_owner
+        }
+
+        /// Hand the right to all future releases over to `to`, e.g. when the grant is sold.
+        /// Clears the split destinations, which were chosen by the previous claim owner.
+        /// Only callable by the current claim owner.
+        #[ink(message)]
+        pub fn transfer_claim(&mut self, to: AccountId) -> Result<()> {
+            if self.env().caller() != self.claim_owner {
+                return Err(Error::NotClaimOwner)
+            }
+            if to == AccountId::from([0u8; 32]) || to == self.env().account_id() {
+                return Err(Error::InvalidClaimOwner)
+            }
+
+            let from = self.claim_owner;
+            self.claim_owner = to;
+            self.split_destinations = Vec::new();
+            self.env().emit_event(ClaimTransferred { from, to });
+            Ok(())
+        }
+
+        /// The account receiving releases that can not be delivered, see `VestingConfig::fallback`
+        #[ink(message)]
+        pub fn fallback(&self) -> Option<AccountId> {
+            self.config.fallback
+        }
+
+        /// The account of the wallet, which receives the funding of the grant
+        #[ink(message)]
+        pub fn contract_address(&self) -> AccountId {
+            self.env().account_id()
+        }
+
+        /// The issuer of the grant, the account that instantiated the wallet
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns true while an acceleration request awaits the owner's approval
+        #[ink(message)]
+        pub fn acceleration_requested(&self) -> bool {
+            self.acceleration_requested
+        }
+
+        /// Ask the owner to vest everything right away. Only callable by the beneficiary.
+        #[ink(message)]
+        pub fn request_acceleration(&mut self) -> Result<()> {
+            if self.env().caller() != self.beneficiary {
+                return Err(Error::NotBeneficiary)
+            }
+
+            self.acceleration_requested = true;
+            self.env().emit_event(AccelerationRequested {
+                beneficiary: self.beneficiary,
+            });
+            Ok(())
+        }
+
+        /// Approve the beneficiary's acceleration request, ending the vesting now so
+        /// everything becomes releasable. Only callable by the owner.
+        #[ink(message)]
+        pub fn approve_acceleration(&mut self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            if self.config.immutable {
+                return Err(Error::Immutable)
+            }
+            if !self.acceleration_requested {
+                return Err(Error::NoAccelerationRequested)
+            }
+
+            let now = self.env().block_timestamp();
+            // a vesting that has not started yet starts now
+            self.start = self.start.min(now);
+            self.duration = now - self.start;
+            self.acceleration_requested = false;
+
+            self.env().emit_event(AccelerationApproved {
+                start: self.start,
+                duration: self.duration,
+            });
+            Ok(())
+        }
+
+        /// Replace the schedule with a new vesting from `new_start` over `new_duration`,
+        /// e.g. for a renegotiated grant. Only callable by the owner.
+        ///
+        /// What was released under the old schedule counts against the new one: nothing is
+        /// releasable until the new schedule has vested more than `released`. Tranches
+        /// describe the old schedule and are removed.
+        #[ink(message)]
+        pub fn rebase(&mut self, new_start: Timestamp, new_duration: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            if self.config.immutable {
+                return Err(Error::Immutable)
+            }
+
+            self.start = new_start;
+            self.duration = new_duration;
+            self.tranches.clear();
+
+            self.env().emit_event(Rebased {
+                start: new_start,
+                duration: new_duration,
+                released: self.released,
+            });
+            Ok(())
+        }
+
+        /// Replace the code of the wallet with the uploaded code of `code_hash`, keeping its
+        /// storage and account. Only callable by the owner. An immutable wallet can not be
+        /// upgraded, as new code could change any of its terms.
+        ///
+        /// The new code has to read the storage written by this version: the fields of
+        /// `VestingWallet` (and of the types stored in it) must keep their order and types.
+        /// New fields can only be appended at the end.
+        #[ink(message)]
+        pub fn set_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            if self.config.immutable {
+                return Err(Error::Immutable)
+            }
+
+            ink_env::set_code_hash(code_hash.as_ref()).map_err(|_| Error::UpgradeFailed)?;
+            self.env().emit_event(CodeUpgraded { code_hash });
+            Ok(())
+        }
+
+        /// Pause releases. Only callable by the owner. An immutable wallet can not be
+        /// paused, as a pause without `auto_unpause_after` would hold back its grant forever.
+        /// See `VestingConfig::auto_unpause_after` for pauses ending on their own.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            if self.config.immutable {
+                return Err(Error::Immutable)
+            }
+
+            let now = self.env().block_timestamp();
+            self.paused_at = Some(now);
+            self.env().emit_event(Paused { at: now });
+            Ok(())
+        }
+
+        /// Lift a pause. Only callable by the owner.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+
+            self.paused_at = None;
+            self.env().emit_event(Unpaused { at: self.env().block_timestamp() });
+            Ok(())
+        }
+
+        /// Returns true while releases are paused, i.e. the owner paused them and
+        /// `auto_unpause_after` (if set) has not passed since
+        #[ink(message)]
+        pub fn paused(&self) -> bool {
+            match (self.paused_at, self.config.auto_unpause_after) {
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(at), Some(after)) => self.env().block_timestamp() < at.saturating_add(after),
+            }
+        }
+
         #[ink(message)]
         pub fn start(&self) -> Timestamp {
             self.start
@@ -58,7 +787,13 @@ mod vesting_wallet {
 
         #[ink(message)]
         pub fn duration(&self) -> u64 {
-            self.start
+            self.duration
+        }
+
+        /// The time the vesting ends, `start + duration`
+        #[ink(message)]
+        pub fn end(&self) -> Timestamp {
+            self.start + self.duration
         }
 
         #[ink(message)]
@@ -66,40 +801,1821 @@ mod vesting_wallet {
             self.released
         }
 
+        /// Decimals of the vested currency
+        #[ink(message)]
+        pub fn decimals(&self) -> u8 {
+            self.config.decimals
+        }
+
+        /// Splits `amount` into its integer and fractional part according to `decimals`,
+        /// e.g. `1_500` with 3 decimals is `(1, 500)`
+        #[ink(message)]
+        pub fn to_display_units(&self, amount: Balance) -> (u128, u128) {
+            let unit = 10u128.pow(self.config.decimals as u32);
+            (amount / unit, amount % unit)
+        }
+
+        /// Number of releases so far. Matches the `nonce` of the latest `TokensReleased` event.
+        #[ink(message)]
+        pub fn release_nonce(&self) -> u64 {
+            self.release_nonce
+        }
+
+        /// Value sent to the wallet through its payable entry points.
+        /// Plain transfers to the contract address are not included.
+        #[ink(message)]
+        pub fn total_funded(&self) -> Balance {
+            self.total_funded
+        }
+
+        /// Returns true once the beneficiary has acknowledged the grant terms
+        #[ink(message)]
+        pub fn acknowledged(&self) -> bool {
+            self.acknowledged
+        }
+
+        /// Accept the terms of the grant. Only callable by the beneficiary.
+        /// Required before the first release when `require_ack` is set.
+        #[ink(message)]
+        pub fn acknowledge(&mut self) -> Result<()> {
+            if self.env().caller() != self.beneficiary {
+                return Err(Error::NotBeneficiary)
+            }
+
+            self.acknowledged = true;
+            Ok(())
+        }
+
+        /// Destinations every release is split between, with their share in basis points
+        #[ink(message)]
+        pub fn split_destinations(&self) -> Vec<(AccountId, u16)> {
+            self.split_destinations.clone()
+        }
+
+        /// Split every release between `destinations`. The shares are in basis points
+        /// and have to add up to 10000. An empty list sends releases to the claim owner.
+        /// Neither the zero account nor the wallet itself can be a destination.
+        /// Only callable by the claim owner.
+        #[ink(message)]
+        pub fn set_split_destinations(&mut self, destinations: Vec<(AccountId, u16)>) -> Result<()> {
+            if self.env().caller() != self.claim_owner {
+                return Err(Error::NotClaimOwner)
+            }
+            let total: u32 = destinations.iter().map(|(_, bps)| *bps as u32).sum();
+            if !destinations.is_empty() && total != 10_000 {
+                return Err(Error::InvalidSplit)
+            }
+            // rejected here, so a release does not fail halfway through the split
+            let wallet = self.env().account_id();
+            if destinations.iter().any(|(to, _)| *to == AccountId::from([0u8; 32]) || *to == wallet) {
+                return Err(Error::InvalidSplit)
+            }
+
+            self.split_destinations = destinations;
+            Ok(())
+        }
+
+        /// Checks whether `caller` can release funds right now, returning the
+        /// reason if not. `release` runs the same checks.
+        #[ink(message)]
+        pub fn can_release(&self, caller: AccountId) -> Result<()> {
+            if self.paused() {
+                return Err(Error::Paused)
+            }
+            if self.config.require_ack && !self.acknowledged {
+                return Err(Error::NotAcknowledged)
+            }
+            if self.config.restrict_release && caller != self.beneficiary && caller != self.claim_owner {
+                return Err(Error::NotBeneficiary)
+            }
+            if self.release_nonce > 0
+                && self.env().block_timestamp() < self.last_release + self.config.min_release_interval {
+                return Err(Error::TooSoon)
+            }
+            if self.releasable() == 0 {
+                return Err(Error::NothingToRelease)
+            }
+            Ok(())
+        }
+
+        /// Releases the vested and not yet released amount to the split destinations,
+        /// or to the claim owner if there are none.
+        /// A failed delivery is retried with the fallback account. If that fails as well,
+        /// the release returns `TransferFailed` when nothing has been sent yet, but panics
+        /// when an earlier split destination has already been paid, so the whole release reverts.
         #[ink(message)]
-        pub fn release(&mut self) {
+        pub fn release(&mut self) -> Result<()> {
+            self.can_release(self.env().caller())?;
+            if !self.is_compliant() {
+                return Err(Error::ComplianceBlocked)
+            }
+
             let releasable = self.vested_amount(self.env().block_timestamp()) - self.released;
+            let legs = self.split(releasable);
+
+            // transfer the payment into the payee's account, before the release is recorded.
+            // Panicking on a later leg reverts the legs already sent, so a split release is
+            // never applied partially.
+            for (leg, (to, amount)) in legs.into_iter().enumerate() {
+                if self.deliver(to, amount).is_ok() {
+                    continue
+                }
+                match self.config.fallback {
+                    Some(fallback) if self.transfer(fallback, amount).is_ok() => {
+                        self.env().emit_event(FallbackDelivery {
+                            intended: to,
+                            fallback,
+                            amount,
+                        });
+                    }
+                    _ if leg == 0 => return Err(Error::TransferFailed),
+                    _ => panic!("requested transfer failed"),
+                }
+            }
+
+            self.record_accrued(releasable);
+            self.last_accrued = 0;
+
             self.released += releasable;
+            self.release_nonce += 1;
+            self.last_release = self.env().block_timestamp();
 
             self.env().emit_event(TokensReleased {
                 amount: releasable,
+                bucket: amount_bucket(releasable),
+                nonce: self.release_nonce,
             });
 
-            // transfer the payment into the payee's account
-            if self.env().transfer(self.beneficiary, releasable).is_err() {
-                panic!("requested transfer failed")
+            Ok(())
+        }
+
+        /// Divides `amount` between the split destinations.
+        /// Rounding dust goes to the first destination.
+        fn split(&self, amount: Balance) -> Vec<(AccountId, Balance)> {
+            if self.split_destinations.is_empty() {
+                return ink_prelude::vec![(self.claim_owner, amount)];
             }
+
+            let mut portions: Vec<(AccountId, Balance)> = self.split_destinations.iter()
+                .map(|(to, bps)| (*to, mul_div(amount, *bps as u128, 10_000).0))
+                .collect();
+            let distributed: Balance = portions.iter().map(|(_, portion)| portion).sum();
+            portions[0].1 += amount - distributed;
+            portions
         }
 
-        #[ink(message)]
-        pub fn vested_amount(&self, timestamp: Timestamp) -> Balance {
-            self.vesting_schedule(self.env().balance() + self.released, timestamp)
+        /// Asks the `compliance_oracle` whether the beneficiary may receive funds.
+        /// An oracle that can not be called counts as a refusal.
+        fn is_compliant(&self) -> bool {
+            let oracle = match self.config.compliance_oracle {
+                Some(oracle) => oracle,
+                None => return true,
+            };
+
+            self.ask_oracle(oracle).unwrap_or(false)
         }
 
-        fn vesting_schedule(&self, total_allocation: Balance, timestamp: Timestamp) -> Balance {
-            if timestamp < self.start {
-                return 0
-            }else if timestamp > self.start + self.duration {
-                return total_allocation;
-            }else{
-                return (total_allocation * (timestamp - self.start) as u128) / self.duration as u128;   
+        /// Calls the oracle's `is_allowed` message with the beneficiary
+        #[cfg(not(test))]
+        fn ask_oracle(&self, oracle: AccountId) -> ink_env::Result<bool> {
+            build_call::<<Self as ::ink_lang::reflect::ContractEnv>::Env>()
+                .call_type(Call::new().callee(oracle))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(self.config.compliance_selector))
+                        .push_arg(self.beneficiary),
+                )
+                .returns::<bool>()
+                .fire()
+        }
+
+        /// Records the question in `ORACLE_QUERIES` and answers from `ORACLE_ANSWERS`.
+        /// The call of an oracle without an answer fails.
+        #[cfg(test)]
+        fn ask_oracle(&self, oracle: AccountId) -> ink_env::Result<bool> {
+            ORACLE_QUERIES.with(|queries| queries.borrow_mut().push((oracle, self.config.compliance_selector, self.beneficiary)));
+            ORACLE_ANSWERS.with(|answers| {
+                answers.borrow().iter()
+                    .find(|(answering, _)| *answering == oracle)
+                    .map(|(_, allowed)| *allowed)
+                    .ok_or(ink_env::Error::CalleeTrapped)
+            })
+        }
+
+        /// Sends `amount` to `to` according to the configured `DeliveryMode`
+        fn deliver(&self, to: AccountId, amount: Balance) -> ink_env::Result<()> {
+            match self.config.delivery {
+                DeliveryMode::Transfer => self.transfer(to, amount),
+                DeliveryMode::Call { selector } => self.call(to, selector, amount),
             }
         }
-        
 
-    }
+        /// Calls the message with `selector` on `to`, transferring `amount`
+        #[cfg(not(test))]
+        fn call(&self, to: AccountId, selector: [u8; 4], amount: Balance) -> ink_env::Result<()> {
+            build_call::<<Self as ::ink_lang::reflect::ContractEnv>::Env>()
+                .call_type(
+                    Call::new()
+                        .callee(to)
+                        .transferred_value(amount),
+                )
+                .exec_input(ExecutionInput::new(Selector::new(selector)))
+                .returns::<()>()
+                .fire()
+        }
 
-    #[cfg(test)]
-    mod tests {
+        /// Records the call in `DELIVERY_CALLS` and transfers `amount` to `to`.
+        /// Calls of the `REJECTING_ACCOUNTS` revert.
+        #[cfg(test)]
+        fn call(&self, to: AccountId, selector: [u8; 4], amount: Balance) -> ink_env::Result<()> {
+            DELIVERY_CALLS.with(|calls| calls.borrow_mut().push((to, selector, amount)));
+            if REJECTING_ACCOUNTS.with(|accounts| accounts.borrow().contains(&to)) {
+                return Err(ink_env::Error::CalleeReverted)
+            }
+            self.env().transfer(to, amount)
+        }
+
+        /// A plain balance transfer of `amount` to `to`
+        #[cfg(not(test))]
+        fn transfer(&self, to: AccountId, amount: Balance) -> ink_env::Result<()> {
+            self.env().transfer(to, amount)
+        }
+
+        /// A plain balance transfer of `amount` to `to`, failing for the `REJECTING_ACCOUNTS`
+        #[cfg(test)]
+        fn transfer(&self, to: AccountId, amount: Balance) -> ink_env::Result<()> {
+            if REJECTING_ACCOUNTS.with(|accounts| accounts.borrow().contains(&to)) {
+                return Err(ink_env::Error::TransferFailed)
+            }
+            self.env().transfer(to, amount)
+        }
+
+        #[ink(message)]
+        pub fn vested_amount(&self, timestamp: Timestamp) -> Balance {
+            self.vesting_schedule(self.total_allocation(), timestamp)
+        }
+
+        /// Returns `(vested_amount, releasable)` at the current time,
+        /// reading the wallet balance only once
+        #[ink(message)]
+        pub fn snapshot(&self) -> (Balance, Balance) {
+            let vested = self.vested_amount(self.env().block_timestamp());
+            (vested, vested.saturating_sub(self.released))
+        }
+
+        /// The schedule of the wallet and what has been released of it
+        #[ink(message)]
+        pub fn export(&self) -> VestingExport {
+            VestingExport {
+                start: self.start,
+                duration: self.duration,
+                tranches: self.tranches.clone(),
+                rounding: self.config.rounding,
+                tail_bonus_bps: self.config.tail_bonus_bps,
+                tail_window: self.config.tail_window,
+                released: self.released,
+            }
+        }
+
+        /// Returns true if `other` describes the same schedule as this wallet, with the
+        /// same amount released. Lets migration tooling verify a replacement wallet
+        /// before switching over to it.
+        #[ink(message)]
+        pub fn schedules_equivalent(&self, other: VestingExport) -> bool {
+            self.export() == other
+        }
+
+        /// Everything the wallet has held: the current balance plus what was released
+        fn total_allocation(&self) -> Balance {
+            #[cfg(test)]
+            BALANCE_READS.with(|reads| reads.set(reads.get() + 1));
+            self.env().balance() + self.released
+        }
+
+        /// Amount that would be paid out by calling `release` now
+        #[ink(message)]
+        pub fn releasable(&self) -> Balance {
+            self.releasable_at(self.env().block_timestamp())
+        }
+
+        /// Amount that has vested but not been released yet, the same as `releasable`.
+        /// `AccrualThresholdCrossed` reports when it reaches the `accrual_milestones`.
+        #[ink(message)]
+        pub fn accrued_unclaimed(&self) -> Balance {
+            self.releasable()
+        }
+
+        /// Emits `AccrualThresholdCrossed` for the milestones `accrued` has reached
+        /// since the last time it was recorded
+        fn record_accrued(&mut self, accrued: Balance) {
+            for milestone in self.config.accrual_milestones.iter() {
+                if self.last_accrued < *milestone && *milestone <= accrued {
+                    self.env().emit_event(AccrualThresholdCrossed {
+                        milestone: *milestone,
+                        accrued,
+                    });
+                }
+            }
+            self.last_accrued = accrued;
+        }
+
+        /// Amount that could be released at `timestamp`.
+        ///
+        /// This is a projection from the current balance and released amount, so it
+        /// assumes no further funding or releases happen before `timestamp`.
+        #[ink(message)]
+        pub fn releasable_at(&self, timestamp: Timestamp) -> Balance {
+            self.vested_amount(timestamp).saturating_sub(self.released)
+        }
+
+        /// The state of the wallet relevant to a keeper in one call
+        #[ink(message)]
+        pub fn keeper_status(&self) -> KeeperStatus {
+            let now = self.env().block_timestamp();
+            let throttle_end = self.last_release + self.config.min_release_interval;
+            let throttled_until = if self.release_nonce > 0 && now < throttle_end {
+                Some(throttle_end)
+            } else {
+                None
+            };
+            let next_unlock = if self.seconds_until_fully_vested() == 0 {
+                None
+            } else {
+                Some(now.max(self.start))
+            };
+
+            KeeperStatus {
+                releasable: self.releasable(),
+                is_paused: self.paused(),
+                throttled_until,
+                next_unlock,
+            }
+        }
+
+        /// The figures of the grant at the current block in one call,
+        /// reading the wallet balance only once
+        #[ink(message)]
+        pub fn claim_summary(&self) -> ClaimSummary {
+            let now = self.env().block_timestamp();
+            let allocation = self.total_allocation();
+            let vested = self.vesting_schedule(allocation, now);
+            let vested_bps = if allocation == 0 {
+                0
+            } else {
+                mul_div(vested, 10_000, allocation).0 as u16
+            };
+
+            ClaimSummary {
+                allocation,
+                released: self.released,
+                releasable: vested.saturating_sub(self.released),
+                // after a `rebase` the new schedule may not have caught up with `released` yet
+                unvested: allocation - vested.max(self.released),
+                vested_bps,
+                fully_vested_at: now + self.seconds_until_fully_vested(),
+            }
+        }
+
+        /// A rough score of the work `release` does, for keepers sizing the gas of the call.
+        /// This is a heuristic, not a weight estimate: 1 for the release itself, plus 1 per
+        /// tranche, plus 1 per transfer (one per split destination, or one to the claim
+        /// owner), counted twice with `DeliveryMode::Call` since every leg calls a contract.
+        #[ink(message)]
+        pub fn release_complexity(&self) -> u32 {
+            let legs = self.split_destinations.len().max(1) as u32;
+            let leg_cost = match self.config.delivery {
+                DeliveryMode::Transfer => 1,
+                DeliveryMode::Call { .. } => 2,
+            };
+            1 + self.tranches.len() as u32 + legs * leg_cost
+        }
+
+        /// Seconds left until everything is vested, 0 once the vesting has ended.
+        /// With tranches this is the end of the last tranche.
+        #[ink(message)]
+        pub fn seconds_until_fully_vested(&self) -> u64 {
+            let end = self.tranches.iter()
+                .map(|t| t.start + t.duration)
+                .max()
+                .unwrap_or(self.start + self.duration);
+            end.saturating_sub(self.env().block_timestamp())
+        }
+
+        /// Samples `vested_amount` at `points` evenly spaced timestamps from `start`
+        /// to the end of the vesting (both included), for charting the curve.
+        /// `points` is capped at `MAX_PREVIEW_POINTS`.
+        #[ink(message)]
+        pub fn schedule_preview(&self, points: u32) -> Vec<(Timestamp, Balance)> {
+            let points = points.min(MAX_PREVIEW_POINTS) as u64;
+            if points == 0 {
+                return Vec::new();
+            }
+            let total_allocation = self.total_allocation();
+            if points == 1 {
+                let end = self.start.saturating_add(self.duration);
+                return ink_prelude::vec![(end, self.vesting_schedule(total_allocation, end))];
+            }
+
+            (0..points)
+                .map(|i| {
+                    // `duration * i` may not fit a timestamp, the offset itself does
+                    let offset = mul_div(self.duration as u128, i as u128, (points - 1) as u128).0 as u64;
+                    let timestamp = self.start.saturating_add(offset);
+                    (timestamp, self.vesting_schedule(total_allocation, timestamp))
+                })
+                .collect()
+        }
+
+        /// Total amount vested at `timestamp` across all tranches.
+        ///
+        /// A wallet without tranches has a single schedule, and returns `vested_amount(timestamp)`.
+        /// Unlike `vested_amount`, the sum is not limited to what the wallet has held.
+        #[ink(message)]
+        pub fn total_vested(&self, timestamp: Timestamp) -> Balance {
+            if self.tranches.is_empty() {
+                return self.vested_amount(timestamp);
+            }
+
+            self.tranche_schedule(timestamp)
+        }
+
+        fn tranche_schedule(&self, timestamp: Timestamp) -> Balance {
+            self.tranches.iter()
+                .map(|t| self.linear_schedule(t.allocation, t.start, t.duration, timestamp))
+                .sum()
+        }
+
+        fn vesting_schedule(&self, total_allocation: Balance, timestamp: Timestamp) -> Balance {
+            // a tranched grant vests the allocations of its tranches, as far as they are funded
+            if !self.tranches.is_empty() {
+                return self.tranche_schedule(timestamp).min(total_allocation)
+            }
+
+            let bonus = mul_div(total_allocation, self.config.tail_bonus_bps as u128, 10_000).0;
+            let vested = self.linear_schedule(total_allocation - bonus, self.start, self.duration, timestamp);
+
+            let tail_start = self.start.saturating_add(self.duration).saturating_sub(self.config.tail_window);
+            if timestamp >= tail_start.max(self.start) {
+                vested + bonus
+            } else {
+                vested
+            }
+        }
+
+        fn linear_schedule(&self, total_allocation: Balance, start: Timestamp, duration: u64, timestamp: Timestamp) -> Balance {
+            if timestamp < start {
+                return 0
+            }else if timestamp >= start.saturating_add(duration) {
+                return total_allocation;
+            }else{
+                let (vested, remainder) = mul_div(total_allocation, (timestamp - start) as u128, duration as u128);
+                return match self.config.rounding {
+                    RoundingMode::Down => vested,
+                    RoundingMode::Up if remainder != 0 => vested + 1,
+                    RoundingMode::Up => vested,
+                };
+            }
+        }
+        
+
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        type Env = ink_env::DefaultEnvironment;
+        type Event = <VestingWallet as ::ink_lang::reflect::ContractEventBase>::Type;
+
+        fn set_wallet_balance(balance: Balance) {
+            ink_env::test::set_account_balance::<Env>(ink_env::test::callee::<Env>(), balance);
+        }
+
+        fn balance_of(account: AccountId) -> Balance {
+            ink_env::test::get_account_balance::<Env>(account).unwrap()
+        }
+
+        //transfers to `account` fail from now on
+        fn reject_transfers_to(account: AccountId) {
+            REJECTING_ACCOUNTS.with(|accounts| accounts.borrow_mut().push(account));
+        }
+
+        fn delivery_calls() -> Vec<(AccountId, [u8; 4], Balance)> {
+            DELIVERY_CALLS.with(|calls| calls.borrow().clone())
+        }
+
+        //the compliance oracle `oracle` answers `allowed` from now on
+        fn answer_as_oracle(oracle: AccountId, allowed: bool) {
+            ORACLE_ANSWERS.with(|answers| answers.borrow_mut().insert(0, (oracle, allowed)));
+        }
+
+        fn oracle_queries() -> Vec<(AccountId, [u8; 4], AccountId)> {
+            ORACLE_QUERIES.with(|queries| queries.borrow().clone())
+        }
+
+        fn fallback_events() -> Vec<FallbackDelivery> {
+            ink_env::test::recorded_events()
+                .filter_map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                        Ok(Event::FallbackDelivery(delivery)) => Some(delivery),
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        fn released_events() -> Vec<TokensReleased> {
+            ink_env::test::recorded_events()
+                .filter_map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                        Ok(Event::TokensReleased(released)) => Some(released),
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        //advance blocks until the off-chain timestamp reaches `timestamp`
+        fn advance_to(timestamp: Timestamp) {
+            while ink_env::block_timestamp::<Env>() < timestamp {
+                ink_env::test::advance_block::<Env>();
+            }
+        }
+
+        #[ink::test]
+        fn release_without_ack_requirement_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            let bob_before = balance_of(accounts.bob);
+
+            advance_to(600);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000);
+            assert_eq!(balance_of(accounts.bob), bob_before + 1000);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "beneficiary is the zero account")]
+        fn zero_beneficiary_is_rejected() {
+            VestingWallet::new(AccountId::from([0u8; 32]), 0, 600);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "beneficiary is the wallet itself")]
+        fn wallet_as_beneficiary_is_rejected() {
+            VestingWallet::new(ink_env::test::callee::<Env>(), 0, 600);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "start is in the past")]
+        fn past_start_is_rejected() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            advance_to(600);
+            VestingWallet::new(accounts.bob, 0, 1200);
+        }
+
+        #[ink::test]
+        fn past_start_is_allowed_when_configured() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1200);
+            advance_to(600);
+            let now = ink_env::block_timestamp::<Env>();
+            let config = VestingConfig { allow_past_start: true, ..Default::default() };
+            let wallet = VestingWallet::new_with_config(accounts.bob, 0, 1200, config);
+            assert_eq!(wallet.releasable(), now as u128);
+
+            let backdated: Vec<BackdatedVesting> = ink_env::test::recorded_events()
+                .filter_map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                        Ok(Event::BackdatedVesting(backdated)) => Some(backdated),
+                        _ => None,
+                    }
+                })
+                .collect();
+            assert_eq!(backdated.len(), 1);
+            assert_eq!(backdated[0].start, 0);
+            assert_eq!(backdated[0].vested, now as u128);
+
+            //a start in the future emits nothing
+            VestingWallet::new_with_config(accounts.bob, now + 600, 1200, VestingConfig { allow_past_start: true, ..Default::default() });
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+        }
+
+        #[ink::test]
+        fn release_requires_ack_when_configured() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { require_ack: true, ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            advance_to(600);
+            assert_eq!(wallet.release(), Err(Error::NotAcknowledged));
+            assert_eq!(wallet.released(), 0);
+
+            //only the beneficiary can acknowledge
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(wallet.acknowledge(), Err(Error::NotBeneficiary));
+            assert_eq!(wallet.acknowledged(), false);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.acknowledge(), Ok(()));
+            assert_eq!(wallet.acknowledged(), true);
+
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000);
+        }
+
+        #[ink::test]
+        fn new_funded_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            //the instantiation value is part of the balance once the constructor runs
+            set_wallet_balance(1000);
+            ink_env::test::set_value_transferred::<Env>(1000);
+            let wallet = VestingWallet::new_funded(accounts.bob, 0, 600);
+
+            assert_eq!(wallet.total_funded(), 1000);
+            assert_eq!(wallet.vested_amount(300), 500);
+            assert_eq!(wallet.vested_amount(600), 1000);
+
+            let unfunded = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(unfunded.total_funded(), 0);
+        }
+
+        #[ink::test]
+        fn is_immutable_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = VestingConfig { immutable: true, ..Default::default() };
+            let mut immutable = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            assert_eq!(immutable.is_immutable(), true);
+
+            //every message changing the terms is rejected, even for the owner
+            assert_eq!(immutable.approve_acceleration(), Err(Error::Immutable));
+            assert_eq!(immutable.rebase(0, 1200), Err(Error::Immutable));
+            assert_eq!(immutable.set_code_hash(Hash::from([0x02; 32])), Err(Error::Immutable));
+            assert_eq!(immutable.pause(), Err(Error::Immutable));
+            assert_eq!((immutable.start(), immutable.duration(), immutable.paused()), (0, 600, false));
+
+            let mutable = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(mutable.is_immutable(), false);
+        }
+
+        #[ink::test]
+        fn fund_up_to_max_allocation_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = VestingConfig { max_allocation: Some(1000), ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            assert_eq!(wallet.max_allocation(), Some(1000));
+
+            ink_env::test::set_value_transferred::<Env>(600);
+            wallet.fund();
+            ink_env::test::set_value_transferred::<Env>(400);
+            wallet.fund();
+            assert_eq!(wallet.total_funded(), 1000);
+
+            //without a cap any amount is accepted
+            let mut uncapped = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(uncapped.max_allocation(), None);
+            ink_env::test::set_value_transferred::<Env>(1_000_000);
+            uncapped.fund();
+            assert_eq!(uncapped.total_funded(), 1_000_000);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "funding exceeds the maximum allocation")]
+        fn fund_above_max_allocation_fails() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = VestingConfig { max_allocation: Some(1000), ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            ink_env::test::set_value_transferred::<Env>(1000);
+            wallet.fund();
+            ink_env::test::set_value_transferred::<Env>(1);
+            wallet.fund();
+        }
+
+        #[ink::test]
+        fn total_vested_sums_tranches() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            //without tranches the single schedule is used
+            assert_eq!(wallet.total_vested(300), wallet.vested_amount(300));
+
+            //a single tranche matching the schedule gives the same result
+            wallet.tranches = vec![Tranche { start: 0, duration: 600, allocation: 1000 }];
+            assert_eq!(wallet.total_vested(300), wallet.vested_amount(300));
+
+            //two overlapping tranches
+            wallet.tranches = vec![
+                Tranche { start: 0, duration: 600, allocation: 600 },
+                Tranche { start: 300, duration: 600, allocation: 400 },
+            ];
+            assert_eq!(wallet.total_vested(0), 0);
+            //300 from the first, nothing yet from the second
+            assert_eq!(wallet.total_vested(300), 300);
+            //600 from the first, 200 from the second
+            assert_eq!(wallet.total_vested(600), 800);
+            assert_eq!(wallet.total_vested(900), 1000);
+        }
+
+        #[ink::test]
+        fn new_tranched_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let wallet = VestingWallet::new_tranched(accounts.bob, vec![
+                Tranche { start: 100, duration: 200, allocation: 200 },
+                Tranche { start: 400, duration: 400, allocation: 800 },
+            ]);
+            assert_eq!(wallet.tranches.len(), 2);
+            assert_eq!(wallet.start(), 100);
+            assert_eq!(wallet.duration, 700);
+
+            assert_eq!(wallet.total_vested(100), 0);
+            assert_eq!(wallet.total_vested(200), 100);
+            //the first tranche is done, the second has not started
+            assert_eq!(wallet.total_vested(400), 200);
+            assert_eq!(wallet.total_vested(600), 600);
+            assert_eq!(wallet.total_vested(800), 1000);
+            assert_eq!(wallet.total_vested(10_000), 1000);
+        }
+
+        #[ink::test]
+        fn tranched_wallet_releases_its_tranches() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new_tranched(accounts.bob, vec![
+                Tranche { start: 100, duration: 200, allocation: 200 },
+                Tranche { start: 400, duration: 400, allocation: 800 },
+            ]);
+            let bob_before = balance_of(accounts.bob);
+
+            //the first tranche and half of the second, not 500/700 of the single schedule
+            advance_to(600);
+            assert_eq!(wallet.releasable(), 600);
+            assert_eq!(wallet.snapshot(), (600, 600));
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 600);
+            assert_eq!(balance_of(accounts.bob), bob_before + 600);
+            assert_eq!(wallet.claim_summary().unvested, 400);
+
+            advance_to(800);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000);
+            assert_eq!(balance_of(accounts.bob), bob_before + 1000);
+        }
+
+        #[ink::test]
+        fn tranched_wallet_vests_at_most_its_balance() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(500);
+            let wallet = VestingWallet::new_tranched(accounts.bob, vec![
+                Tranche { start: 0, duration: 600, allocation: 1000 },
+            ]);
+            assert_eq!(wallet.total_vested(600), 1000);
+            assert_eq!(wallet.vested_amount(600), 500);
+            assert_eq!(wallet.vested_amount(300), 500);
+            assert_eq!(wallet.vested_amount(120), 200);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "no tranches")]
+        fn new_tranched_rejects_empty_tranches() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            VestingWallet::new_tranched(accounts.bob, Vec::new());
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "tranche with zero duration")]
+        fn new_tranched_rejects_zero_duration() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            VestingWallet::new_tranched(accounts.bob, vec![
+                Tranche { start: 0, duration: 600, allocation: 500 },
+                Tranche { start: 600, duration: 0, allocation: 500 },
+            ]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "tranche end overflows")]
+        fn new_tranched_rejects_overflowing_end() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            VestingWallet::new_tranched(accounts.bob, vec![
+                Tranche { start: 100, duration: 200, allocation: 200 },
+                Tranche { start: u64::MAX - 100, duration: 200, allocation: 800 },
+            ]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "tranche allocations overflow")]
+        fn new_tranched_rejects_overflowing_allocations() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            VestingWallet::new_tranched(accounts.bob, vec![
+                Tranche { start: 100, duration: 200, allocation: Balance::MAX },
+                Tranche { start: 400, duration: 400, allocation: 1 },
+            ]);
+        }
+
+        #[ink::test]
+        fn schedule_preview_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let wallet = VestingWallet::new(accounts.bob, 100, 600);
+
+            let preview = wallet.schedule_preview(7);
+            assert_eq!(preview.len(), 7);
+            assert_eq!(preview[0], (100, 0));
+            assert_eq!(preview[1], (200, 166));
+            assert_eq!(preview[6], (700, 1000));
+            for i in 1..preview.len() {
+                assert!(preview[i].0 > preview[i - 1].0);
+                assert!(preview[i].1 >= preview[i - 1].1);
+            }
+
+            assert_eq!(wallet.schedule_preview(0), Vec::new());
+            assert_eq!(wallet.schedule_preview(1), vec![(700, 1000)]);
+            assert_eq!(wallet.schedule_preview(u32::MAX).len(), MAX_PREVIEW_POINTS as usize);
+        }
+
+        #[ink::test]
+        fn schedule_preview_does_not_overflow() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            //`duration * i` overflows from the second point on
+            let wallet = VestingWallet::new(accounts.bob, 100, u64::MAX - 100);
+            let preview = wallet.schedule_preview(3);
+            assert_eq!(preview[0], (100, 0));
+            assert_eq!(preview[1].0, 100 + (u64::MAX - 100) / 2);
+            assert_eq!(preview[2], (u64::MAX, 1000));
+
+            //the end itself is past the last timestamp
+            let wallet = VestingWallet::new(accounts.bob, u64::MAX - 100, 200);
+            assert_eq!(wallet.schedule_preview(1), vec![(u64::MAX, 1000)]);
+            assert_eq!(wallet.schedule_preview(2), vec![(u64::MAX - 100, 0), (u64::MAX, 1000)]);
+        }
+
+        #[ink::test]
+        fn releasable_at_follows_the_curve() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 100, 1000);
+
+            assert_eq!(wallet.releasable_at(0), 0);
+            assert_eq!(wallet.releasable_at(100), 0);
+            assert_eq!(wallet.releasable_at(350), 250);
+            assert_eq!(wallet.releasable_at(600), 500);
+            assert_eq!(wallet.releasable_at(1100), 1000);
+            assert_eq!(wallet.releasable_at(5000), 1000);
+
+            //already released funds are not projected again
+            advance_to(600);
+            let now = ink_env::block_timestamp::<Env>();
+            wallet.release().unwrap();
+            assert_eq!(wallet.releasable_at(now), 0);
+            assert_eq!(wallet.releasable_at(1100), 1000 - wallet.released());
+            assert_eq!(wallet.releasable(), 0);
+        }
+
+        #[ink::test]
+        fn releasable_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let wallet = VestingWallet::new(accounts.bob, 120, 600);
+
+            //before the start
+            assert_eq!(wallet.releasable(), 0);
+            //mid-vesting
+            advance_to(420);
+            assert_eq!(wallet.releasable(), wallet.vested_amount(420) - wallet.released());
+            assert_eq!(wallet.releasable(), 500);
+            //after the end
+            advance_to(900);
+            assert_eq!(wallet.releasable(), 1000);
+        }
+
+        #[ink::test]
+        fn releasable_saturates_when_the_balance_shrinks() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            advance_to(300);
+            wallet.release().unwrap();
+            assert_eq!(wallet.released(), 500);
+
+            //the remaining balance disappears, so only 250 of the released 500 count as vested
+            set_wallet_balance(0);
+            assert_eq!(wallet.vested_amount(300), 250);
+            assert_eq!(wallet.releasable(), 0);
+            assert_eq!(wallet.release(), Err(Error::NothingToRelease));
+        }
+
+        #[ink::test]
+        fn seconds_until_fully_vested_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut wallet = VestingWallet::new(accounts.bob, 100, 600);
+
+            //before the start
+            assert_eq!(wallet.seconds_until_fully_vested(), 700);
+
+            advance_to(400);
+            let now = ink_env::block_timestamp::<Env>();
+            assert_eq!(wallet.seconds_until_fully_vested(), 700 - now);
+
+            advance_to(700);
+            assert_eq!(wallet.seconds_until_fully_vested(), 0);
+            advance_to(1000);
+            assert_eq!(wallet.seconds_until_fully_vested(), 0);
+
+            //the last tranche ends at 1300
+            wallet.tranches = vec![
+                Tranche { start: 0, duration: 600, allocation: 600 },
+                Tranche { start: 300, duration: 1000, allocation: 400 },
+            ];
+            let now = ink_env::block_timestamp::<Env>();
+            assert_eq!(wallet.seconds_until_fully_vested(), 1300 - now);
+        }
+
+        #[ink::test]
+        fn can_release_reports_each_blocking_condition() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { require_ack: true, restrict_release: true, ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 100, 600, config);
+
+            assert_eq!(wallet.can_release(accounts.bob), Err(Error::NotAcknowledged));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.acknowledge().unwrap();
+
+            assert_eq!(wallet.can_release(accounts.charlie), Err(Error::NotBeneficiary));
+            //nothing has vested before the start
+            assert_eq!(wallet.can_release(accounts.bob), Err(Error::NothingToRelease));
+            assert_eq!(wallet.release(), Err(Error::NothingToRelease));
+
+            advance_to(400);
+            assert_eq!(wallet.can_release(accounts.bob), Ok(()));
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(wallet.release(), Err(Error::NotBeneficiary));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.release(), Ok(()));
+            //everything vested so far has been released
+            assert_eq!(wallet.can_release(accounts.bob), Err(Error::NothingToRelease));
+        }
+
+        #[ink::test]
+        fn release_nonce_increments() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+
+            for timestamp in [100, 200, 300] {
+                advance_to(timestamp);
+                wallet.release().unwrap();
+            }
+
+            let nonces: Vec<u64> = released_events().iter().map(|e| e.nonce).collect();
+            assert_eq!(nonces, vec![1, 2, 3]);
+            assert_eq!(wallet.release_nonce(), 3);
+        }
+
+        #[ink::test]
+        fn split_destinations_work() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(999);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+
+            //shares have to add up to 100%
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.set_split_destinations(vec![(accounts.charlie, 9_000), (accounts.django, 900)]), Err(Error::InvalidSplit));
+            ink_env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(wallet.set_split_destinations(vec![(accounts.charlie, 10_000)]), Err(Error::NotClaimOwner));
+
+            //destinations that can not receive a leg are rejected up front
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            let wallet_account = ink_env::test::callee::<Env>();
+            assert_eq!(wallet.set_split_destinations(vec![(accounts.charlie, 9_000), (wallet_account, 1_000)]), Err(Error::InvalidSplit));
+            assert_eq!(wallet.set_split_destinations(vec![(accounts.charlie, 9_000), (AccountId::from([0u8; 32]), 1_000)]), Err(Error::InvalidSplit));
+            assert_eq!(wallet.split_destinations(), Vec::new());
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            let splits = vec![(accounts.charlie, 9_000), (accounts.django, 1_000)];
+            assert_eq!(wallet.set_split_destinations(splits.clone()), Ok(()));
+            assert_eq!(wallet.split_destinations(), splits);
+
+            let bob_before = balance_of(accounts.bob);
+            let charlie_before = balance_of(accounts.charlie);
+            let django_before = balance_of(accounts.django);
+            advance_to(600);
+            wallet.release().unwrap();
+
+            //899.1 and 99.9, the dust goes to the first destination
+            assert_eq!(balance_of(accounts.charlie), charlie_before + 900);
+            assert_eq!(balance_of(accounts.django), django_before + 99);
+            assert_eq!(balance_of(accounts.bob), bob_before);
+            assert_eq!(wallet.released(), 999);
+        }
+
+        #[ink::test]
+        fn failed_second_leg_reverts_the_release() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.set_split_destinations(vec![(accounts.charlie, 9_000), (accounts.django, 1_000)]), Ok(()));
+            reject_transfers_to(accounts.django);
+            let django_before = balance_of(accounts.django);
+
+            //the first leg was already sent, so the release panics instead of returning an error
+            advance_to(600);
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| wallet.release()));
+            assert!(result.is_err());
+
+            //nothing is recorded. The off-chain environment does not roll back the first leg,
+            //on-chain the panic reverts it as well.
+            assert_eq!((wallet.released(), wallet.release_nonce()), (0, 0));
+            assert_eq!(balance_of(accounts.django), django_before);
+            assert_eq!(released_events().len(), 0);
+        }
+
+        #[ink::test]
+        fn to_display_units_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = VestingConfig { decimals: 12, ..Default::default() };
+            let wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            assert_eq!(wallet.decimals(), 12);
+
+            assert_eq!(wallet.to_display_units(0), (0, 0));
+            assert_eq!(wallet.to_display_units(999_999_999_999), (0, 999_999_999_999));
+            assert_eq!(wallet.to_display_units(1_000_000_000_000), (1, 0));
+            assert_eq!(wallet.to_display_units(1_000_000_000_001), (1, 1));
+            assert_eq!(wallet.to_display_units(u128::MAX), (u128::MAX / 10u128.pow(12), u128::MAX % 10u128.pow(12)));
+
+            //without decimals everything is integer
+            let plain = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(plain.to_display_units(1_234), (1_234, 0));
+        }
+
+        #[ink::test]
+        fn released_event_bucket_matches_magnitude() {
+            assert_eq!(amount_bucket(0), 0);
+            assert_eq!(amount_bucket(9), 0);
+            assert_eq!(amount_bucket(10), 1);
+            assert_eq!(amount_bucket(999), 2);
+            assert_eq!(amount_bucket(1_000), 3);
+            assert_eq!(amount_bucket(u128::MAX), 38);
+
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(100_000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            //releases of 1000 and 99000
+            advance_to(6);
+            wallet.release().unwrap();
+            advance_to(600);
+            wallet.release().unwrap();
+
+            let events = released_events();
+            assert_eq!((events[0].amount, events[0].bucket), (1_000, 3));
+            assert_eq!((events[1].amount, events[1].bucket), (99_000, 4));
+        }
+
+        #[ink::test]
+        fn min_release_interval_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { min_release_interval: 60, ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            advance_to(60);
+            assert_eq!(wallet.release(), Ok(()));
+            let last_release = ink_env::block_timestamp::<Env>();
+
+            ink_env::test::advance_block::<Env>();
+            assert_eq!(wallet.can_release(accounts.bob), Err(Error::TooSoon));
+            assert_eq!(wallet.release(), Err(Error::TooSoon));
+
+            advance_to(last_release + 60);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.release_nonce(), 2);
+        }
+
+        #[ink::test]
+        fn snapshot_reads_the_balance_once() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let wallet = VestingWallet::new(accounts.bob, 0, 600);
+            advance_to(300);
+
+            BALANCE_READS.with(|reads| reads.set(0));
+            let separate = (wallet.vested_amount(ink_env::block_timestamp::<Env>()), wallet.releasable());
+            assert_eq!(BALANCE_READS.with(|reads| reads.get()), 2);
+
+            BALANCE_READS.with(|reads| reads.set(0));
+            assert_eq!(wallet.snapshot(), separate);
+            assert_eq!(BALANCE_READS.with(|reads| reads.get()), 1);
+
+            BALANCE_READS.with(|reads| reads.set(0));
+            wallet.schedule_preview(MAX_PREVIEW_POINTS);
+            assert_eq!(BALANCE_READS.with(|reads| reads.get()), 1);
+        }
+
+        #[ink::test]
+        fn acceleration_needs_both_parties() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            //alice instantiates the wallet
+            let mut wallet = VestingWallet::new(accounts.bob, 60, 600);
+            assert_eq!(wallet.owner(), accounts.alice);
+            advance_to(360);
+            let vested = wallet.releasable();
+
+            //the owner can not accelerate on its own
+            assert_eq!(wallet.approve_acceleration(), Err(Error::NoAccelerationRequested));
+            assert_eq!(wallet.request_acceleration(), Err(Error::NotBeneficiary));
+
+            //the beneficiary can only ask
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.request_acceleration(), Ok(()));
+            assert_eq!(wallet.acceleration_requested(), true);
+            assert_eq!(wallet.approve_acceleration(), Err(Error::NotOwner));
+            assert_eq!(wallet.releasable(), vested);
+            assert_eq!(wallet.duration, 600);
+
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(wallet.approve_acceleration(), Ok(()));
+            assert_eq!(wallet.acceleration_requested(), false);
+            assert_eq!(wallet.releasable(), 1000);
+            assert_eq!(wallet.start, 60);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000);
+        }
+
+        #[ink::test]
+        fn acceleration_before_start_vests_everything() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 600, 600);
+            let mut immutable = VestingWallet::new_with_config(accounts.bob, 600, 600, VestingConfig { immutable: true, ..Default::default() });
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.request_acceleration().unwrap();
+            immutable.request_acceleration().unwrap();
+
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(wallet.approve_acceleration(), Ok(()));
+            assert_eq!(wallet.releasable(), 1000);
+            //an immutable grant can not be accelerated
+            assert_eq!(immutable.approve_acceleration(), Err(Error::Immutable));
+            assert_eq!(immutable.releasable(), 0);
+        }
+
+        #[ink::test]
+        fn rebase_keeps_released() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            let bob_before = balance_of(accounts.bob);
+            advance_to(300);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.release().unwrap();
+            assert_eq!(wallet.released(), 500);
+
+            assert_eq!(wallet.rebase(300, 1200), Err(Error::NotOwner));
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(wallet.rebase(300, 1200), Ok(()));
+            assert_eq!((wallet.start(), wallet.duration), (300, 1200));
+
+            //the new schedule vests 1000 over 1200 seconds, the first 500 were already paid
+            assert_eq!(wallet.releasable(), 0);
+            assert_eq!(wallet.releasable_at(600), 0);
+            assert_eq!(wallet.releasable_at(900), 0);
+            assert_eq!(wallet.releasable_at(1200), 250);
+            let summary = wallet.claim_summary();
+            assert_eq!(summary.released + summary.releasable + summary.unvested, summary.allocation);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            advance_to(600);
+            assert_eq!(wallet.release(), Err(Error::NothingToRelease));
+            advance_to(1200);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 750);
+            assert_eq!(balance_of(accounts.bob), bob_before + 750);
+        }
+
+        #[ink::test]
+        fn immutable_wallet_can_not_be_rebased() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, VestingConfig { immutable: true, ..Default::default() });
+            assert_eq!(wallet.rebase(0, 1200), Err(Error::Immutable));
+            assert_eq!(wallet.duration, 600);
+        }
+
+        #[ink::test]
+        fn immutable_wallet_can_not_be_paused() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, VestingConfig { immutable: true, ..Default::default() });
+            assert_eq!(wallet.pause(), Err(Error::Immutable));
+            assert_eq!(wallet.paused(), false);
+
+            advance_to(600);
+            assert_eq!(wallet.release(), Ok(()));
+        }
+
+        #[ink::test]
+        fn pause_blocks_release() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.pause(), Err(Error::NotOwner));
+            ink_env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(wallet.pause(), Ok(()));
+
+            //without `auto_unpause_after` the pause lasts until the owner lifts it
+            advance_to(6_000);
+            assert_eq!(wallet.paused(), true);
+            assert_eq!(wallet.release(), Err(Error::Paused));
+
+            assert_eq!(wallet.unpause(), Ok(()));
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000);
+        }
+
+        #[ink::test]
+        fn pause_ends_after_auto_unpause_after() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { auto_unpause_after: Some(120), ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            advance_to(60);
+            let paused_at = ink_env::block_timestamp::<Env>();
+            wallet.pause().unwrap();
+
+            advance_to(paused_at + 114);
+            assert_eq!(wallet.paused(), true);
+            assert_eq!(wallet.can_release(accounts.bob), Err(Error::Paused));
+
+            //the owner did not respond, the pause ends on its own
+            advance_to(paused_at + 120);
+            assert_eq!(wallet.paused(), false);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000 * (paused_at + 120) as u128 / 600);
+        }
+
+        #[ink::test]
+        fn fallback_is_only_used_for_failed_deliveries() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { fallback: Some(accounts.eve), ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            assert_eq!(wallet.fallback(), Some(accounts.eve));
+            let bob_before = balance_of(accounts.bob);
+            let eve_before = balance_of(accounts.eve);
+
+            //bob accepts the transfer, nothing is redirected
+            advance_to(600);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(balance_of(accounts.bob), bob_before + 1000);
+            assert_eq!(balance_of(accounts.eve), eve_before);
+            assert_eq!(fallback_events().len(), 0);
+        }
+
+        #[ink::test]
+        fn failed_delivery_goes_to_the_fallback() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { fallback: Some(accounts.eve), ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            reject_transfers_to(accounts.bob);
+            let bob_before = balance_of(accounts.bob);
+            let eve_before = balance_of(accounts.eve);
+
+            advance_to(600);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(wallet.released(), 1000);
+            assert_eq!(balance_of(accounts.bob), bob_before);
+            assert_eq!(balance_of(accounts.eve), eve_before + 1000);
+
+            let redirected = fallback_events();
+            assert_eq!(redirected.len(), 1);
+            assert_eq!((redirected[0].intended, redirected[0].fallback, redirected[0].amount), (accounts.bob, accounts.eve, 1000));
+        }
+
+        #[ink::test]
+        fn rejecting_contract_beneficiary_goes_to_the_fallback() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig {
+                delivery: DeliveryMode::Call { selector: [0x0a; 4] },
+                fallback: Some(accounts.eve),
+                ..Default::default()
+            };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            reject_transfers_to(accounts.bob);
+            let bob_before = balance_of(accounts.bob);
+            let eve_before = balance_of(accounts.eve);
+
+            //the beneficiary's message reverts, the fallback gets a plain transfer
+            advance_to(600);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(delivery_calls(), vec![(accounts.bob, [0x0a; 4], 1000)]);
+            assert_eq!(balance_of(accounts.bob), bob_before);
+            assert_eq!(balance_of(accounts.eve), eve_before + 1000);
+            assert_eq!(wallet.released(), 1000);
+
+            let redirected = fallback_events();
+            assert_eq!(redirected.len(), 1);
+            assert_eq!((redirected[0].intended, redirected[0].fallback, redirected[0].amount), (accounts.bob, accounts.eve, 1000));
+        }
+
+        #[ink::test]
+        fn failed_fallback_fails_the_release() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { fallback: Some(accounts.eve), ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            let mut without_fallback = VestingWallet::new(accounts.bob, 0, 600);
+            reject_transfers_to(accounts.bob);
+            reject_transfers_to(accounts.eve);
+            let eve_before = balance_of(accounts.eve);
+
+            advance_to(600);
+            assert_eq!(wallet.release(), Err(Error::TransferFailed));
+            assert_eq!(without_fallback.release(), Err(Error::TransferFailed));
+            assert_eq!((wallet.released(), wallet.release_nonce()), (0, 0));
+            assert_eq!(without_fallback.released(), 0);
+            assert_eq!(balance_of(accounts.eve), eve_before);
+            assert_eq!(balance_of(ink_env::test::callee::<Env>()), 1000);
+            assert_eq!(released_events().len(), 0);
+        }
+
+        #[ink::test]
+        fn schedules_equivalent_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 60, 600);
+            let export = wallet.export();
+
+            //identical
+            assert!(wallet.schedules_equivalent(export.clone()));
+            let copy = VestingWallet::new(accounts.charlie, 60, 600);
+            assert!(copy.schedules_equivalent(export.clone()));
+
+            //slightly different
+            assert!(!wallet.schedules_equivalent(VestingExport { duration: 601, ..export.clone() }));
+            assert!(!wallet.schedules_equivalent(VestingExport { rounding: RoundingMode::Up, ..export.clone() }));
+
+            //clearly different
+            let tranches = vec![Tranche { start: 0, duration: 60, allocation: 1000 }];
+            assert!(!wallet.schedules_equivalent(VestingExport { start: 0, duration: 6_000, tranches, ..export.clone() }));
+
+            //a release has to be mirrored as well
+            advance_to(360);
+            wallet.release().unwrap();
+            assert!(!wallet.schedules_equivalent(export.clone()));
+            assert!(wallet.schedules_equivalent(VestingExport { released: 500, ..export }));
+        }
+
+        #[ink::test]
+        fn schedules_with_different_tail_bonuses_differ() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = VestingConfig { tail_bonus_bps: 2_000, tail_window: 60, ..Default::default() };
+            let wallet = VestingWallet::new_with_config(accounts.bob, 60, 600, config.clone());
+            let export = wallet.export();
+            assert_eq!((export.tail_bonus_bps, export.tail_window), (2_000, 60));
+
+            let without_bonus = VestingWallet::new(accounts.bob, 60, 600);
+            assert!(!without_bonus.schedules_equivalent(export.clone()));
+            let other_bonus = VestingWallet::new_with_config(accounts.bob, 60, 600, VestingConfig { tail_bonus_bps: 1_000, ..config.clone() });
+            assert!(!other_bonus.schedules_equivalent(export.clone()));
+            let other_window = VestingWallet::new_with_config(accounts.bob, 60, 600, VestingConfig { tail_window: 120, ..config.clone() });
+            assert!(!other_window.schedules_equivalent(export.clone()));
+
+            let copy = VestingWallet::new_with_config(accounts.charlie, 60, 600, config);
+            assert!(copy.schedules_equivalent(export));
+        }
+
+        #[ink::test]
+        fn releases_follow_the_claim_owner() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(wallet.claim_owner(), accounts.bob);
+            let bob_before = balance_of(accounts.bob);
+            let charlie_before = balance_of(accounts.charlie);
+
+            advance_to(300);
+            wallet.release().unwrap();
+            assert_eq!(balance_of(accounts.bob), bob_before + 500);
+
+            //only the claim owner can hand the claim over
+            assert_eq!(wallet.transfer_claim(accounts.charlie), Err(Error::NotClaimOwner));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.transfer_claim(AccountId::from([0u8; 32])), Err(Error::InvalidClaimOwner));
+            assert_eq!(wallet.transfer_claim(accounts.charlie), Ok(()));
+            assert_eq!(wallet.claim_owner(), accounts.charlie);
+            assert_eq!(wallet.transfer_claim(accounts.django), Err(Error::NotClaimOwner));
+
+            advance_to(600);
+            wallet.release().unwrap();
+            assert_eq!(balance_of(accounts.bob), bob_before + 500);
+            assert_eq!(balance_of(accounts.charlie), charlie_before + 500);
+            //the grantee is kept for the record
+            assert_eq!(wallet.beneficiary(), accounts.bob);
+        }
+
+        #[ink::test]
+        fn transfer_claim_clears_split_destinations() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.set_split_destinations(vec![(accounts.django, 10_000)]).unwrap();
+
+            wallet.transfer_claim(accounts.charlie).unwrap();
+            assert_eq!(wallet.split_destinations(), Vec::new());
+            //the new claim owner decides on splits
+            assert_eq!(wallet.set_split_destinations(vec![(accounts.bob, 10_000)]), Err(Error::NotClaimOwner));
+        }
+
+        #[ink::test]
+        fn tail_bonus_unlocks_in_the_tail_window() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { tail_bonus_bps: 2_000, tail_window: 60, ..Default::default() };
+            let wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            //only the linear 80% vest during the term
+            assert_eq!(wallet.vested_amount(0), 0);
+            assert_eq!(wallet.vested_amount(300), 400);
+            assert_eq!(wallet.vested_amount(539), 718);
+            //the 200 bonus unlocks with the tail window
+            assert_eq!(wallet.vested_amount(540), 720 + 200);
+            assert_eq!(wallet.vested_amount(599), 798 + 200);
+            assert_eq!(wallet.vested_amount(600), 1000);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "tail bonus above 100%")]
+        fn tail_bonus_above_allocation_is_rejected() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let config = VestingConfig { tail_bonus_bps: 10_001, ..Default::default() };
+            VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+        }
+
+        #[ink::test]
+        fn set_code_hash_needs_owner_of_mutable_wallet() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            let mut immutable = VestingWallet::new_with_config(accounts.bob, 0, 600, VestingConfig { immutable: true, ..Default::default() });
+
+            assert_eq!(immutable.set_code_hash(Hash::from([0x02; 32])), Err(Error::Immutable));
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(wallet.set_code_hash(Hash::from([0x02; 32])), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn accrual_milestones_are_reported() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { accrual_milestones: vec![100, 500], ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            let crossed = || -> Vec<(Balance, Balance)> {
+                ink_env::test::recorded_events()
+                    .filter_map(|event| {
+                        match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                            Ok(Event::AccrualThresholdCrossed(e)) => Some((e.milestone, e.accrued)),
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            };
+
+            advance_to(120);
+            assert_eq!(wallet.accrued_unclaimed(), 200);
+            wallet.release().unwrap();
+            assert_eq!(crossed(), vec![(100, 200)]);
+            assert_eq!(wallet.accrued_unclaimed(), 0);
+
+            //funding records the accrual as well
+            advance_to(480);
+            assert_eq!(wallet.accrued_unclaimed(), 600);
+            wallet.fund();
+            assert_eq!(crossed(), vec![(100, 200), (100, 600), (500, 600)]);
+
+            //milestones already reported are not reported again
+            advance_to(540);
+            wallet.fund();
+            assert_eq!(crossed().len(), 3);
+        }
+
+        #[ink::test]
+        fn duration_and_end_work() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let wallet = VestingWallet::new(accounts.bob, 60, 600);
+            assert_eq!(wallet.start(), 60);
+            assert_eq!(wallet.duration(), 600);
+            assert_eq!(wallet.end(), 660);
+        }
+
+        #[ink::test]
+        fn new_with_end_matches_new() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let by_duration = VestingWallet::new(accounts.bob, 60, 600);
+            let by_end = VestingWallet::new_with_end(accounts.bob, 60, 660);
+
+            assert_eq!(by_end.duration(), 600);
+            assert_eq!(by_end.end(), 660);
+            assert!(by_end.schedules_equivalent(by_duration.export()));
+            for timestamp in [0, 60, 300, 659, 660, 1000] {
+                assert_eq!(by_end.vested_amount(timestamp), by_duration.vested_amount(timestamp));
+            }
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "end is not after start")]
+        fn new_with_end_rejects_end_before_start() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            VestingWallet::new_with_end(accounts.bob, 600, 600);
+        }
+
+        #[ink::test]
+        fn contract_address_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let wallet = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(wallet.contract_address(), ink_env::test::callee::<Env>());
+        }
+
+        #[ink::test]
+        fn keeper_status_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(0);
+            let config = VestingConfig { min_release_interval: 60, ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 120, 600, config);
+
+            //unfunded, not started
+            assert_eq!(wallet.keeper_status(), KeeperStatus {
+                releasable: 0,
+                is_paused: false,
+                throttled_until: None,
+                next_unlock: Some(120),
+            });
+
+            //funded and vesting
+            set_wallet_balance(1000);
+            advance_to(420);
+            assert_eq!(wallet.keeper_status().releasable, 500);
+            assert_eq!(wallet.keeper_status().next_unlock, Some(420));
+
+            //throttled after a release
+            wallet.release().unwrap();
+            assert_eq!(wallet.keeper_status().releasable, 0);
+            assert_eq!(wallet.keeper_status().throttled_until, Some(480));
+
+            //paused
+            wallet.pause().unwrap();
+            advance_to(720);
+            assert_eq!(wallet.keeper_status(), KeeperStatus {
+                releasable: 500,
+                is_paused: true,
+                throttled_until: None,
+                next_unlock: None,
+            });
+            wallet.unpause().unwrap();
+            assert_eq!(wallet.keeper_status().is_paused, false);
+        }
+
+        #[ink::test]
+        fn release_complexity_scales_with_the_schedule() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            assert_eq!(wallet.release_complexity(), 2);
+
+            wallet.tranches = vec![Tranche { start: 0, duration: 600, allocation: 500 }; 3];
+            assert_eq!(wallet.release_complexity(), 5);
+
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.set_split_destinations(vec![(accounts.charlie, 5_000), (accounts.django, 5_000)]).unwrap();
+            assert_eq!(wallet.release_complexity(), 6);
+
+            //every leg calls the destination contract
+            let config = VestingConfig { delivery: DeliveryMode::Call { selector: [0; 4] }, ..Default::default() };
+            let mut calling = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            calling.set_split_destinations(vec![(accounts.charlie, 5_000), (accounts.django, 5_000)]).unwrap();
+            assert_eq!(calling.release_complexity(), 5);
+        }
+
+        #[ink::test]
+        fn releases_add_up_to_the_allocation() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            //1001 is not divisible by the duration, every partial release truncates
+            set_wallet_balance(1001);
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            let bob_before = balance_of(accounts.bob);
+
+            for timestamp in [66, 132, 402, 594] {
+                advance_to(timestamp);
+                wallet.release().unwrap();
+            }
+            assert!(wallet.released() < 1001);
+
+            advance_to(600);
+            wallet.release().unwrap();
+            assert_eq!(wallet.released(), 1001);
+            assert_eq!(balance_of(accounts.bob), bob_before + 1001);
+            assert_eq!(balance_of(ink_env::test::callee::<Env>()), 0);
+            assert_eq!(wallet.releasable(), 0);
+        }
+
+        #[ink::test]
+        fn claim_summary_is_consistent() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let mut wallet = VestingWallet::new(accounts.bob, 60, 600);
+            advance_to(180);
+            wallet.release().unwrap();
+
+            advance_to(462);
+            BALANCE_READS.with(|reads| reads.set(0));
+            let summary = wallet.claim_summary();
+            assert_eq!(BALANCE_READS.with(|reads| reads.get()), 1);
+            assert_eq!(summary, ClaimSummary {
+                allocation: 1000,
+                released: 200,
+                releasable: 470,
+                unvested: 330,
+                vested_bps: 6_700,
+                fully_vested_at: 660,
+            });
+            assert_eq!(summary.released + summary.releasable + summary.unvested, summary.allocation);
+        }
+
+        #[ink::test]
+        fn rounding_mode_works() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let down = VestingWallet::new(accounts.bob, 0, 600);
+            let config = VestingConfig { rounding: RoundingMode::Up, ..Default::default() };
+            let up = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            //1000 * 100 / 600 = 166.66..
+            assert_eq!(down.vested_amount(100), 166);
+            assert_eq!(up.vested_amount(100), 167);
+            //evenly divisible amounts are not affected
+            assert_eq!(down.vested_amount(300), 500);
+            assert_eq!(up.vested_amount(300), 500);
+            //both vest exactly the allocation at the end
+            assert_eq!(down.vested_amount(600), 1000);
+            assert_eq!(up.vested_amount(600), 1000);
+            assert_eq!(up.vested_amount(700), 1000);
+        }
+
+        #[ink::test]
+        fn compliance_oracle_is_only_asked_for_a_possible_release() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { compliance_oracle: Some(accounts.django), compliance_selector: [0x01; 4], ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 60, 600, config);
+
+            //nothing has vested, the release fails without calling the oracle
+            assert_eq!(wallet.release(), Err(Error::NothingToRelease));
+            assert_eq!(oracle_queries(), Vec::new());
+        }
+
+        #[ink::test]
+        fn compliance_oracle_is_asked_before_release() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { compliance_oracle: Some(accounts.django), compliance_selector: [0x01; 4], ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            let bob_before = balance_of(accounts.bob);
+
+            //the oracle allows the release
+            answer_as_oracle(accounts.django, true);
+            advance_to(300);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(oracle_queries(), vec![(accounts.django, [0x01; 4], accounts.bob)]);
+            assert_eq!(balance_of(accounts.bob), bob_before + 500);
+
+            //and then blocks it
+            answer_as_oracle(accounts.django, false);
+            advance_to(600);
+            assert_eq!(wallet.release(), Err(Error::ComplianceBlocked));
+            assert_eq!(oracle_queries().len(), 2);
+            assert_eq!(balance_of(accounts.bob), bob_before + 500);
+            assert_eq!(wallet.released(), 500);
+        }
+
+        #[ink::test]
+        fn failing_compliance_oracle_blocks_the_release() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { compliance_oracle: Some(accounts.django), compliance_selector: [0x01; 4], ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            //django has no answer, so the call fails
+            advance_to(300);
+            assert_eq!(wallet.release(), Err(Error::ComplianceBlocked));
+            assert_eq!(oracle_queries().len(), 1);
+            assert_eq!(wallet.released(), 0);
+        }
+
+        #[ink::test]
+        fn call_delivery_calls_the_beneficiary() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(1000);
+            let config = VestingConfig { delivery: DeliveryMode::Call { selector: [0x0a; 4] }, ..Default::default() };
+            let mut wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+            let bob_before = balance_of(accounts.bob);
+
+            advance_to(300);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(delivery_calls(), vec![(accounts.bob, [0x0a; 4], 500)]);
+            assert_eq!(balance_of(accounts.bob), bob_before + 500);
+            assert_eq!(wallet.released(), 500);
+
+            //every split destination is called with its share
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.set_split_destinations(vec![(accounts.charlie, 6_000), (accounts.django, 4_000)]).unwrap();
+            advance_to(600);
+            assert_eq!(wallet.release(), Ok(()));
+            assert_eq!(delivery_calls()[1..], [(accounts.charlie, [0x0a; 4], 300), (accounts.django, [0x0a; 4], 200)]);
+        }
+
+        #[ink::test]
+        fn large_allocation_does_not_overflow() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(Balance::MAX);
+            let down = VestingWallet::new(accounts.bob, 0, 600);
+            let config = VestingConfig { rounding: RoundingMode::Up, ..Default::default() };
+            let up = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            //Balance::MAX is odd
+            assert_eq!(down.vested_amount(300), Balance::MAX / 2);
+            assert_eq!(up.vested_amount(300), Balance::MAX / 2 + 1);
+
+            let mut last = 0;
+            for timestamp in (0..=600).step_by(30) {
+                let vested = down.vested_amount(timestamp);
+                assert!(vested >= last);
+                last = vested;
+            }
+            assert_eq!(last, Balance::MAX);
+        }
+
+        #[ink::test]
+        fn large_allocation_with_tail_bonus_does_not_overflow() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(Balance::MAX);
+            let config = VestingConfig { tail_bonus_bps: 2_500, tail_window: 100, ..Default::default() };
+            let wallet = VestingWallet::new_with_config(accounts.bob, 0, 600, config);
+
+            let bonus = Balance::MAX / 4;
+            assert_eq!(wallet.vested_amount(250), (Balance::MAX - bonus) / 600 * 250 + (Balance::MAX - bonus) % 600 * 250 / 600);
+            assert_eq!(wallet.vested_amount(500), Balance::MAX - bonus - (Balance::MAX - bonus) / 6 + bonus);
+            assert_eq!(wallet.vested_amount(600), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn claim_summary_of_a_large_allocation_does_not_overflow() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            set_wallet_balance(Balance::MAX);
+            let wallet = VestingWallet::new(accounts.bob, 0, 600);
+
+            advance_to(300);
+            let summary = wallet.claim_summary();
+            //Balance::MAX is odd, half of it is just below 50%
+            assert_eq!(summary.vested_bps, 4_999);
+            assert_eq!(summary.releasable, Balance::MAX / 2);
+            advance_to(600);
+            assert_eq!(wallet.claim_summary().vested_bps, 10_000);
+        }
+
+        #[ink::test]
+        fn split_of_a_large_amount_does_not_overflow() {
+            let accounts = ink_env::test::default_accounts::<Env>();
+            let mut wallet = VestingWallet::new(accounts.bob, 0, 600);
+            ink_env::test::set_caller::<Env>(accounts.bob);
+            wallet.set_split_destinations(vec![(accounts.charlie, 3_333), (accounts.django, 6_667)]).unwrap();
+
+            let legs = wallet.split(Balance::MAX);
+            let django = Balance::MAX / 10_000 * 6_667 + Balance::MAX % 10_000 * 6_667 / 10_000;
+            assert_eq!(legs, vec![(accounts.charlie, Balance::MAX - django), (accounts.django, django)]);
+        }
+
+        #[ink::test]
+        fn mul_div_works() {
+            assert_eq!(mul_div(1000, 100, 600), (166, 400));
+            assert_eq!(mul_div(1000, 300, 600), (500, 0));
+            assert_eq!(mul_div(u128::MAX, 1, 2), (u128::MAX / 2, 1));
+            assert_eq!(mul_div(u128::MAX, 599, 600), (u128::MAX - u128::MAX / 600 - 1, 345));
+            //`r * b` of the remainder would overflow
+            assert_eq!(mul_div(u128::MAX / 2, 10_000, u128::MAX), (4_999, u128::MAX - 5_000));
+            assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), (u128::MAX, 0));
+            assert_eq!(mul_div(7, 0, 3), (0, 0));
+        }
     }
 }